@@ -0,0 +1,50 @@
+//! Time source and delay provider used by the driver
+
+use core::time::Duration;
+
+/// Monotonic clock and blocking delay used for timeouts and protocol pauses
+///
+/// With the `std` feature [`StdClock`] is used by default. On bare-metal targets,
+/// implement this on top of the platform's timer and pass it to
+/// [`UhfRfid::with_clock`](crate::UhfRfid::with_clock).
+pub trait Clock {
+    /// Time elapsed since an arbitrary fixed point; must never go backwards
+    fn now(&self) -> Duration;
+
+    /// Block for the given duration
+    fn delay(&mut self, duration: Duration);
+}
+
+/// Clock backed by `std::time::Instant` and `std::thread::sleep`
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct StdClock {
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl StdClock {
+    pub fn new() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for StdClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn delay(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
@@ -0,0 +1,128 @@
+//! Discovery of readers on the host's serial ports
+
+use std::thread;
+use std::time::Duration;
+
+use log::debug;
+
+use crate::reader::UhfRfid;
+use crate::serial::SerialTransport;
+use crate::types::{BaudRate, ModuleInfo, UhfError};
+
+/// Settings for [`discover_with`]
+#[derive(Debug, Clone)]
+pub struct DiscoveryOptions {
+    /// Baud rates to try on each port, in order
+    pub baud_rates: Vec<BaudRate>,
+    /// Time allowed for probing all baud rates on one port
+    pub port_timeout: Duration,
+}
+
+impl Default for DiscoveryOptions {
+    fn default() -> Self {
+        Self {
+            // Module default first, then the rest from fastest to slowest
+            baud_rates: vec![
+                BaudRate::B115200,
+                BaudRate::B57600,
+                BaudRate::B38400,
+                BaudRate::B19200,
+                BaudRate::B9600,
+            ],
+            port_timeout: Duration::from_secs(1),
+        }
+    }
+}
+
+/// A reader found by [`discover`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredReader {
+    /// Serial port name, e.g. `/dev/ttyUSB0`
+    pub port_name: String,
+    /// Baud rate the reader answered at
+    pub baud_rate: BaudRate,
+    /// Hardware version, if the reader answered the query
+    pub hardware_version: Option<String>,
+    /// Firmware version, if the reader answered the query
+    pub firmware_version: Option<String>,
+    /// Manufacturer string, if the reader answered the query
+    pub manufacturer: Option<String>,
+}
+
+impl DiscoveredReader {
+    /// Full module info, if the reader answered all three queries
+    pub fn module_info(&self) -> Option<ModuleInfo> {
+        Some(ModuleInfo {
+            hardware_version: self.hardware_version.clone()?,
+            firmware_version: self.firmware_version.clone()?,
+            manufacturer: self.manufacturer.clone()?,
+        })
+    }
+}
+
+/// Find readers on all serial ports with default options
+pub fn discover() -> Result<Vec<DiscoveredReader>, UhfError> {
+    discover_with(&DiscoveryOptions::default())
+}
+
+/// Find readers on all serial ports
+///
+/// Every port reported by `serialport::available_ports` is probed in parallel
+/// with a firmware version query at each candidate baud rate. Ports that cannot
+/// be opened or do not answer are skipped. A port that answers the probe is
+/// reported even if some of the follow-up version queries fail.
+pub fn discover_with(options: &DiscoveryOptions) -> Result<Vec<DiscoveredReader>, UhfError> {
+    let ports = serialport::available_ports().map_err(|e| UhfError::Transport(e.to_string()))?;
+
+    let found = thread::scope(|scope| {
+        let probes: Vec<_> = ports
+            .iter()
+            .map(|port| scope.spawn(move || probe_port(&port.port_name, options)))
+            .collect();
+
+        probes
+            .into_iter()
+            .filter_map(|probe| probe.join().ok().flatten())
+            .collect()
+    });
+
+    Ok(found)
+}
+
+fn probe_port(port_name: &str, options: &DiscoveryOptions) -> Option<DiscoveredReader> {
+    let first_baud = options.baud_rates.first()?;
+    let probe_timeout = options.port_timeout / options.baud_rates.len() as u32;
+
+    let port = match serialport::new(port_name, first_baud.bps()).timeout(probe_timeout).open() {
+        Ok(port) => port,
+        Err(e) => {
+            debug!("Skipping {}: {}", port_name, e);
+            return None;
+        }
+    };
+
+    let mut rfid = UhfRfid::new(SerialTransport::from_port(port));
+    rfid.set_default_response_timeout(probe_timeout);
+
+    let baud_rate = match rfid.detect_baud_rate(&options.baud_rates) {
+        Ok(baud_rate) => baud_rate,
+        Err(e) => {
+            debug!("No reader on {}: {:?}", port_name, e);
+            return None;
+        }
+    };
+
+    Some(DiscoveredReader {
+        port_name: port_name.to_string(),
+        baud_rate,
+        hardware_version: query(port_name, "hardware version", rfid.get_hardware_version()),
+        firmware_version: query(port_name, "firmware version", rfid.get_firmware_version()),
+        manufacturer: query(port_name, "manufacturer", rfid.get_manufacturer()),
+    })
+}
+
+fn query(port_name: &str, what: &str, result: Result<String, UhfError>) -> Option<String> {
+    result
+        .map_err(|e| debug!("No {} from {}: {:?}", what, port_name, e))
+        .ok()
+}
@@ -0,0 +1,69 @@
+//! Transport for bare-metal targets using the `embedded-io` serial traits
+
+use core::time::Duration;
+use embedded_io::{Read, ReadReady, Write};
+
+use crate::clock::Clock;
+use crate::transport::RfidTransport;
+
+/// Transport over any UART implementing `embedded-io` [`Read`], [`Write`] and [`ReadReady`]
+///
+/// `embedded-io` reads block until data arrives, so read timeouts are implemented
+/// by polling [`ReadReady`] against the given clock.
+pub struct EmbeddedHalTransport<U, C> {
+    uart: U,
+    clock: C,
+}
+
+impl<U, C> EmbeddedHalTransport<U, C>
+where
+    U: Read + Write + ReadReady,
+    C: Clock,
+{
+    // Pause between readiness checks while waiting for data
+    const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+    pub fn new(uart: U, clock: C) -> Self {
+        Self { uart, clock }
+    }
+
+    /// Give back the UART and clock
+    pub fn release(self) -> (U, C) {
+        (self.uart, self.clock)
+    }
+}
+
+impl<U, C> RfidTransport for EmbeddedHalTransport<U, C>
+where
+    U: Read + Write + ReadReady,
+    C: Clock,
+{
+    type Error = U::Error;
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error> {
+        self.uart.write_all(data)?;
+        self.uart.flush()?;
+        Ok(data.len())
+    }
+
+    fn read(&mut self, buf: &mut [u8], timeout_ms: u32) -> Result<usize, Self::Error> {
+        let deadline = self.clock.now() + Duration::from_millis(timeout_ms as u64);
+
+        while !self.uart.read_ready()? {
+            if self.clock.now() >= deadline {
+                return Ok(0);
+            }
+            self.clock.delay(Self::POLL_INTERVAL);
+        }
+
+        self.uart.read(buf)
+    }
+
+    fn clear_input(&mut self) -> Result<(), Self::Error> {
+        let mut scratch = [0u8; 32];
+        while self.uart.read_ready()? {
+            self.uart.read(&mut scratch)?;
+        }
+        Ok(())
+    }
+}
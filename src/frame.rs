@@ -0,0 +1,389 @@
+//! Allocation-free frame encoding and parsing
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::transport::RfidTransport;
+use crate::types::{Rssi, UhfError};
+
+pub(crate) const HEADER: u8 = 0xBB;
+pub(crate) const END: u8 = 0x7E;
+pub(crate) const CMD_TYPE: u8 = 0x00;
+pub(crate) const RESP_TYPE_TAG: u8 = 0x02;
+pub(crate) const ERROR_COMMAND: u8 = 0xFF;
+
+// Commands that carry an access or kill password, shared with the frame redaction
+pub(crate) const READ_TAG_DATA: u8 = 0x39;
+pub(crate) const WRITE_TAG_DATA: u8 = 0x49;
+pub(crate) const KILL_TAG: u8 = 0x65;
+pub(crate) const LOCK_TAG: u8 = 0x82;
+pub(crate) const BLOCK_PERMALOCK: u8 = 0xD3;
+pub(crate) const NXP_CHANGE_CONFIG: u8 = 0xE0;
+pub(crate) const NXP_READ_PROTECT: u8 = 0xE1;
+pub(crate) const NXP_RESET_READ_PROTECT: u8 = 0xE2;
+pub(crate) const NXP_CHANGE_EAS: u8 = 0xE3;
+pub(crate) const IMPINJ_MONZA_QT: u8 = 0xE5;
+
+/// Bytes a frame adds around its parameters: header, type, command, length (2), checksum, end
+pub const FRAME_OVERHEAD: usize = 7;
+
+/// Largest EPC that fits the 5-bit length field of the PC word (31 words)
+pub const MAX_EPC_BYTES: usize = 62;
+
+/// Encode a command frame into a caller-provided buffer
+///
+/// # Returns
+/// Number of bytes written, `params.len() + FRAME_OVERHEAD`
+pub fn encode_command_into(command: u8, params: &[u8], buf: &mut [u8]) -> Result<usize, UhfError> {
+    let Ok(param_len) = u16::try_from(params.len()) else {
+        return Err(UhfError::InvalidParameter("Too many command parameters".into()));
+    };
+
+    let frame_len = params.len() + FRAME_OVERHEAD;
+    if buf.len() < frame_len {
+        return Err(UhfError::InvalidParameter(alloc::format!(
+            "Buffer too small for command frame: need {} bytes, have {}",
+            frame_len,
+            buf.len()
+        )));
+    }
+
+    let [msb, lsb] = param_len.to_be_bytes();
+    buf[..5].copy_from_slice(&[HEADER, CMD_TYPE, command, msb, lsb]);
+    buf[5..5 + params.len()].copy_from_slice(params);
+    buf[frame_len - 2] = checksum(&buf[1..frame_len - 2]);
+    buf[frame_len - 1] = END;
+
+    Ok(frame_len)
+}
+
+/// Frame checksum: sum of type, command, length and parameter bytes, truncated to 8 bits
+pub(crate) fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |acc, &b| acc.wrapping_add(b))
+}
+
+/// Result of checking received bytes that start with a frame header
+pub(crate) enum FrameCheck {
+    /// More bytes are needed to tell
+    Incomplete,
+    /// Bad length, checksum or end marker; resync on the next header
+    Invalid,
+    /// A valid frame of this many bytes
+    Complete(usize),
+}
+
+/// Check whether `pending`, starting at a header byte, begins with a complete valid frame
+///
+/// Frames claiming to be longer than `max_len` are invalid.
+pub(crate) fn check_frame(pending: &[u8], max_len: usize) -> FrameCheck {
+    if pending.len() < 5 {
+        return FrameCheck::Incomplete;
+    }
+
+    let frame_len = u16::from_be_bytes([pending[3], pending[4]]) as usize + FRAME_OVERHEAD;
+    if frame_len > max_len {
+        return FrameCheck::Invalid;
+    }
+    if pending.len() < frame_len {
+        return FrameCheck::Incomplete;
+    }
+
+    let frame = &pending[..frame_len];
+    if frame[frame_len - 1] != END || frame[frame_len - 2] != checksum(&frame[1..frame_len - 2]) {
+        return FrameCheck::Invalid;
+    }
+    FrameCheck::Complete(frame_len)
+}
+
+/// A complete, checksum-verified frame borrowed from a [`FrameBuffer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameRef<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> FrameRef<'a> {
+    /// Frame type (0x00 command, 0x01 response, 0x02 notification)
+    pub fn frame_type(&self) -> u8 {
+        self.bytes[1]
+    }
+
+    /// Command code, or 0xFF for error responses
+    pub fn command(&self) -> u8 {
+        self.bytes[2]
+    }
+
+    /// Parameter bytes between the length field and the checksum
+    pub fn params(&self) -> &'a [u8] {
+        &self.bytes[5..self.bytes.len() - 2]
+    }
+
+    /// The whole frame, header to end marker
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Error code if this is an error response
+    pub fn error_code(&self) -> Option<u8> {
+        match (self.command(), self.params().first()) {
+            (ERROR_COMMAND, Some(&code)) => Some(code),
+            _ => None,
+        }
+    }
+
+    /// Tag data if this is a tag notification
+    pub fn tag(&self) -> Option<TagRef<'a>> {
+        let params = self.params();
+        if self.frame_type() != RESP_TYPE_TAG || params.len() < 5 {
+            return None;
+        }
+
+        // RSSI (1) + PC (2) + EPC + CRC (2)
+        Some(TagRef {
+            rssi: Rssi::from_raw(params[0]),
+            pc: u16::from_be_bytes([params[1], params[2]]),
+            epc: &params[3..params.len() - 2],
+        })
+    }
+}
+
+/// A complete, checksum-verified frame owned by the caller
+///
+/// Returned by [`UhfRfid::send_raw`](crate::UhfRfid::send_raw).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    bytes: Vec<u8>,
+}
+
+impl Frame {
+    /// Check the header, length field, checksum and end marker of a received frame
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, UhfError> {
+        let complete = match check_frame(&bytes, bytes.len()) {
+            FrameCheck::Complete(len) => len == bytes.len(),
+            _ => false,
+        };
+        let valid = complete && bytes.first() == Some(&HEADER);
+        if !valid {
+            return Err(UhfError::InvalidResponse(alloc::format!("Invalid frame: {:02X?}", bytes)));
+        }
+        Ok(Self { bytes })
+    }
+
+    /// Wrap bytes that [`check_frame`] already accepted
+    pub(crate) fn checked(bytes: Vec<u8>) -> Self {
+        Self { bytes }
+    }
+
+    /// Borrow the frame as a [`FrameRef`]
+    pub fn as_frame_ref(&self) -> FrameRef<'_> {
+        FrameRef { bytes: &self.bytes }
+    }
+
+    /// Frame type (0x01 response, 0x02 notification)
+    pub fn frame_type(&self) -> u8 {
+        self.as_frame_ref().frame_type()
+    }
+
+    /// Command code, or 0xFF for error responses
+    pub fn command(&self) -> u8 {
+        self.as_frame_ref().command()
+    }
+
+    /// Parameter bytes between the length field and the checksum
+    pub fn params(&self) -> &[u8] {
+        self.as_frame_ref().params()
+    }
+
+    /// Error code if this is an error response
+    pub fn error_code(&self) -> Option<u8> {
+        self.as_frame_ref().error_code()
+    }
+
+    /// Tag data if this is a tag notification
+    pub fn tag(&self) -> Option<TagRef<'_>> {
+        self.as_frame_ref().tag()
+    }
+
+    /// The whole frame, header to end marker
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Give back the frame bytes
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Tag notification data borrowed from a received frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagRef<'a> {
+    /// Signal strength of the reply
+    pub rssi: Rssi,
+    /// Protocol control word
+    pub pc: u16,
+    /// EPC bytes
+    pub epc: &'a [u8],
+}
+
+impl TagRef<'_> {
+    /// Copy into a fixed-capacity tag that outlives the receive buffer
+    #[cfg(feature = "heapless")]
+    pub fn to_heapless(&self) -> Result<HeaplessTag, UhfError> {
+        let epc = heapless::Vec::from_slice(self.epc)
+            .map_err(|_| UhfError::InvalidResponse("EPC longer than 62 bytes".into()))?;
+        Ok(HeaplessTag {
+            rssi: self.rssi,
+            pc: self.pc,
+            epc,
+        })
+    }
+
+    /// Convert into an owned [`TagInfo`](crate::TagInfo)
+    pub fn to_tag_info(&self) -> crate::TagInfo {
+        crate::TagInfo {
+            epc: crate::types::bytes_to_hex(self.epc),
+            rssi: self.rssi,
+        }
+    }
+}
+
+/// Owned tag data without heap allocation
+#[cfg(feature = "heapless")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaplessTag {
+    /// Signal strength of the reply
+    pub rssi: Rssi,
+    /// Protocol control word
+    pub pc: u16,
+    /// EPC bytes
+    pub epc: heapless::Vec<u8, MAX_EPC_BYTES>,
+}
+
+/// Fixed-capacity receive buffer that splits incoming bytes into frames
+///
+/// Consumed bytes are reclaimed by moving the unread tail to the front, so a
+/// frame is always contiguous and can be handed out as a borrowed [`FrameRef`].
+/// Noise, truncated frames and frames with a bad checksum are skipped.
+pub struct FrameBuffer<const N: usize> {
+    buf: [u8; N],
+    start: usize,
+    end: usize,
+    discarded: usize,
+    rejected_frames: usize,
+}
+
+impl<const N: usize> FrameBuffer<N> {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            start: 0,
+            end: 0,
+            discarded: 0,
+            rejected_frames: 0,
+        }
+    }
+
+    /// Number of buffered bytes not yet returned as frames
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Drop all buffered bytes
+    pub fn clear(&mut self) {
+        self.start = 0;
+        self.end = 0;
+    }
+
+    /// Number of bytes skipped so far because they did not form a valid frame
+    pub fn discarded_bytes(&self) -> usize {
+        self.discarded
+    }
+
+    /// Number of frame candidates rejected for a bad length, checksum or end marker
+    pub fn discarded_frames(&self) -> usize {
+        self.rejected_frames
+    }
+
+    /// Append bytes, returning how many fit
+    pub fn extend_from_slice(&mut self, data: &[u8]) -> usize {
+        let space = self.spare_capacity();
+        let len = data.len().min(space.len());
+        space[..len].copy_from_slice(&data[..len]);
+        self.end += len;
+        len
+    }
+
+    /// Read whatever the transport has available into the free space
+    pub fn fill_from<T: RfidTransport>(&mut self, transport: &mut T, timeout_ms: u32) -> Result<usize, T::Error> {
+        let space = self.spare_capacity();
+        if space.is_empty() {
+            return Ok(0);
+        }
+
+        let len = transport.read(space, timeout_ms)?;
+        self.end += len;
+        Ok(len)
+    }
+
+    /// Take the next complete frame, if one has been received
+    pub fn next_frame(&mut self) -> Option<FrameRef<'_>> {
+        self.next_frame_range().map(|range| self.frame_at(range))
+    }
+
+    /// Locate and consume the next complete frame, returning its position
+    ///
+    /// The bytes stay in place until more data is added to the buffer.
+    pub(crate) fn next_frame_range(&mut self) -> Option<Range<usize>> {
+        loop {
+            let pending = &self.buf[self.start..self.end];
+            let Some(offset) = pending.iter().position(|&b| b == HEADER) else {
+                self.discard(pending.len());
+                return None;
+            };
+            self.discard(offset);
+
+            match check_frame(&self.buf[self.start..self.end], N) {
+                FrameCheck::Incomplete => return None,
+                FrameCheck::Invalid => {
+                    self.rejected_frames += 1;
+                    self.discard(1);
+                }
+                FrameCheck::Complete(frame_len) => {
+                    let frame_start = self.start;
+                    self.start += frame_len;
+                    return Some(frame_start..frame_start + frame_len);
+                }
+            }
+        }
+    }
+
+    /// Frame previously located by [`next_frame_range`](Self::next_frame_range)
+    pub(crate) fn frame_at(&self, range: Range<usize>) -> FrameRef<'_> {
+        FrameRef { bytes: &self.buf[range] }
+    }
+
+    fn discard(&mut self, count: usize) {
+        self.start += count;
+        self.discarded += count;
+    }
+
+    /// Free space after the buffered bytes, compacting first if needed
+    fn spare_capacity(&mut self) -> &mut [u8] {
+        if self.start > 0 {
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+        &mut self.buf[self.end..]
+    }
+}
+
+impl<const N: usize> Default for FrameBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
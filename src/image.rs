@@ -0,0 +1,322 @@
+//! Tag memory images and their JSON file format
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::types::{MemoryBank, UhfError};
+
+/// Value of the `format` field in image files
+const FORMAT_NAME: &str = "uhf-rfid tag memory image";
+/// Version of the image file format written by [`TagMemoryImage::to_json`]
+const FORMAT_VERSION: u32 = 1;
+/// Words per line in image files
+const WORDS_PER_LINE: usize = 8;
+
+const LOCKED: &str = "locked";
+const OUT_OF_RANGE: &str = "out-of-range";
+
+/// One 16-bit word of a tag memory image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryWord {
+    /// Word content
+    Data(u16),
+    /// The tag refused to read the word (read-locked or wrong access password)
+    Locked,
+    /// The word lies past the end of the bank; nothing follows it
+    OutOfRange,
+}
+
+impl MemoryWord {
+    /// Word content, if it was readable
+    pub fn data(self) -> Option<u16> {
+        match self {
+            Self::Data(word) => Some(word),
+            _ => None,
+        }
+    }
+}
+
+/// Snapshot of a tag's memory banks, see [`UhfRfid::dump_tag`](crate::UhfRfid::dump_tag)
+///
+/// Each bank lists its words from address 0. A bank that ended before the dump's
+/// word limit finishes with [`MemoryWord::OutOfRange`].
+///
+/// Images are stored as JSON with one hex string per word, eight words per line,
+/// so that files diff cleanly:
+///
+/// ```text
+/// {
+///   "format": "uhf-rfid tag memory image",
+///   "version": 1,
+///   "reserved": [
+///     "locked", "locked", "0000", "0000", "out-of-range"
+///   ],
+///   "epc": [
+///     "A1B2", "3000", ...
+///   ],
+///   "tid": [],
+///   "user": []
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagMemoryImage {
+    /// Kill and access passwords
+    pub reserved: Vec<MemoryWord>,
+    /// StoredCRC, PC and EPC
+    pub epc: Vec<MemoryWord>,
+    /// Tag identification
+    pub tid: Vec<MemoryWord>,
+    /// User memory
+    pub user: Vec<MemoryWord>,
+}
+
+impl TagMemoryImage {
+    /// Words of one bank
+    pub fn bank(&self, bank: MemoryBank) -> &[MemoryWord] {
+        match bank {
+            MemoryBank::Reserved => &self.reserved,
+            MemoryBank::Epc => &self.epc,
+            MemoryBank::Tid => &self.tid,
+            MemoryBank::User => &self.user,
+        }
+    }
+
+    /// Mutable words of one bank
+    pub fn bank_mut(&mut self, bank: MemoryBank) -> &mut Vec<MemoryWord> {
+        match bank {
+            MemoryBank::Reserved => &mut self.reserved,
+            MemoryBank::Epc => &mut self.epc,
+            MemoryBank::Tid => &mut self.tid,
+            MemoryBank::User => &mut self.user,
+        }
+    }
+
+    /// Encode the image in the JSON file format
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        let _ = writeln!(json, "{{");
+        let _ = writeln!(json, "  \"format\": \"{}\",", FORMAT_NAME);
+        let _ = writeln!(json, "  \"version\": {},", FORMAT_VERSION);
+
+        let banks = BANK_KEYS.iter().map(|(key, bank)| (*key, self.bank(*bank)));
+        for (index, (key, words)) in banks.enumerate() {
+            let separator = if index + 1 < BANK_KEYS.len() { "," } else { "" };
+            if words.is_empty() {
+                let _ = writeln!(json, "  \"{}\": []{}", key, separator);
+                continue;
+            }
+
+            let _ = writeln!(json, "  \"{}\": [", key);
+            let lines = words.chunks(WORDS_PER_LINE).collect::<Vec<_>>();
+            for (line_index, line) in lines.iter().enumerate() {
+                let cells: Vec<String> =
+                    line.iter().map(|word| format!("\"{}\"", word_to_str(*word))).collect();
+                let comma = if line_index + 1 < lines.len() { "," } else { "" };
+                let _ = writeln!(json, "    {}{}", cells.join(", "), comma);
+            }
+            let _ = writeln!(json, "  ]{}", separator);
+        }
+
+        json.push_str("}\n");
+        json
+    }
+
+    /// Decode an image from the JSON file format
+    ///
+    /// Unknown fields are ignored so newer files with additional metadata still load.
+    pub fn from_json(json: &str) -> Result<Self, UhfError> {
+        let mut parser = Parser { input: json.as_bytes(), pos: 0 };
+        let root = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos != parser.input.len() {
+            return Err(parser.error("trailing characters"));
+        }
+
+        let Value::Object(fields) = root else {
+            return Err(invalid("image must be a JSON object"));
+        };
+        let field =
+            |name: &str| fields.iter().find(|(key, _)| key == name).map(|(_, value)| value);
+
+        match field("format") {
+            Some(Value::String(format)) if format == FORMAT_NAME => {}
+            _ => return Err(invalid("not a tag memory image")),
+        }
+        match field("version") {
+            Some(Value::Number(version)) if *version == FORMAT_VERSION => {}
+            _ => return Err(invalid("unsupported image version")),
+        }
+
+        let mut image = Self::default();
+        for (key, bank) in BANK_KEYS {
+            let Some(Value::Array(items)) = field(key) else {
+                return Err(invalid(&format!("missing \"{}\" bank", key)));
+            };
+            let words = image.bank_mut(bank);
+            for item in items {
+                let Value::String(text) = item else {
+                    return Err(invalid(&format!("\"{}\" words must be strings", key)));
+                };
+                words.push(word_from_str(text)?);
+            }
+        }
+
+        Ok(image)
+    }
+}
+
+/// Outcome of [`UhfRfid::restore_tag`](crate::UhfRfid::restore_tag)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreReport {
+    /// Words written to the tag
+    pub written_words: usize,
+    /// Bank and word address of words the tag refused to write
+    pub locked_words: Vec<(MemoryBank, u8)>,
+}
+
+const BANK_KEYS: [(&str, MemoryBank); 4] = [
+    ("reserved", MemoryBank::Reserved),
+    ("epc", MemoryBank::Epc),
+    ("tid", MemoryBank::Tid),
+    ("user", MemoryBank::User),
+];
+
+fn word_to_str(word: MemoryWord) -> String {
+    match word {
+        MemoryWord::Data(data) => format!("{:04X}", data),
+        MemoryWord::Locked => LOCKED.into(),
+        MemoryWord::OutOfRange => OUT_OF_RANGE.into(),
+    }
+}
+
+fn word_from_str(text: &str) -> Result<MemoryWord, UhfError> {
+    match text {
+        LOCKED => Ok(MemoryWord::Locked),
+        OUT_OF_RANGE => Ok(MemoryWord::OutOfRange),
+        _ if text.len() == 4 => u16::from_str_radix(text, 16)
+            .map(MemoryWord::Data)
+            .map_err(|_| invalid(&format!("invalid word \"{}\"", text))),
+        _ => Err(invalid(&format!("invalid word \"{}\"", text))),
+    }
+}
+
+fn invalid(message: &str) -> UhfError {
+    UhfError::InvalidParameter(format!("Invalid tag memory image: {}", message))
+}
+
+/// The subset of JSON used by image files
+enum Value {
+    Object(Vec<(String, Value)>),
+    Array(Vec<Value>),
+    String(String),
+    Number(u32),
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Result<Value, UhfError> {
+        self.skip_whitespace();
+        match self.input.get(self.pos) {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'0'..=b'9') => self.number(),
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, UhfError> {
+        self.pos += 1;
+        let mut fields = Vec::new();
+        if self.consume(b'}') {
+            return Ok(Value::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            if !self.consume(b':') {
+                return Err(self.error("expected ':'"));
+            }
+            fields.push((key, self.value()?));
+            if self.consume(b'}') {
+                return Ok(Value::Object(fields));
+            }
+            if !self.consume(b',') {
+                return Err(self.error("expected ',' or '}'"));
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, UhfError> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        if self.consume(b']') {
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            if self.consume(b']') {
+                return Ok(Value::Array(items));
+            }
+            if !self.consume(b',') {
+                return Err(self.error("expected ',' or ']'"));
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, UhfError> {
+        if self.input.get(self.pos) != Some(&b'"') {
+            return Err(self.error("expected a string"));
+        }
+        let start = self.pos + 1;
+        let Some(len) = self.input[start..].iter().position(|&b| b == b'"') else {
+            return Err(self.error("unterminated string"));
+        };
+        let text = &self.input[start..start + len];
+        if text.contains(&b'\\') {
+            return Err(self.error("escape sequences are not supported"));
+        }
+        self.pos = start + len + 1;
+        core::str::from_utf8(text)
+            .map(String::from)
+            .map_err(|_| self.error("invalid UTF-8"))
+    }
+
+    fn number(&mut self) -> Result<Value, UhfError> {
+        let start = self.pos;
+        while self.input.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        core::str::from_utf8(&self.input[start..self.pos])
+            .ok()
+            .and_then(|digits| digits.parse().ok())
+            .map(Value::Number)
+            .ok_or_else(|| self.error("invalid number"))
+    }
+
+    fn consume(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        if self.input.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.input.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self, message: &str) -> UhfError {
+        invalid(&format!("{} at byte {}", message, self.pos))
+    }
+}
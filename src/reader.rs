@@ -1,23 +1,87 @@
-use log::{debug, error, warn};
-use std::time::{Duration, Instant};
-
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+use core::time::Duration;
+use log::{debug, error, info, warn};
+
+use crate::clock::Clock;
+#[cfg(feature = "std")]
+use crate::clock::StdClock;
+use crate::frame::{self, Frame, FrameBuffer, FrameCheck, TagRef, FRAME_OVERHEAD};
+use crate::image::{MemoryWord, RestoreReport, TagMemoryImage};
+use crate::redact::Redacted;
+use crate::trace;
 use crate::transport::RfidTransport;
 use crate::types::{
-    bytes_to_hex, LockPayload, MemoryBank, QtControl, QueryParams, Region, RfLinkProfile,
-    SelectAction, SelectMode, SelectParams, SelectTarget, TagInfo, UhfError,
+    bytes_to_hex, AdaptiveInventory, AdaptiveQ, AdaptiveQConfig, BaudRate, Confirmation,
+    DemodulatorParams, GuardMode, HoppingPlan, InventoryOptions, InventoryStats, InventoryStrategy,
+    IoDirection, IoLevel, IoPin, LockAction, LockPayload, LockState, LockStatus, MemoryBank,
+    ModuleInfo, NxpConfigFlag, NxpConfigWord, Password, QtControl, QtProfile, QueryParams, QuerySel,
+    QueryTarget, Region, RfLinkProfile, Rssi, SelectAction, SelectMode, SelectParams, SelectTarget,
+    SpectrumReport, TagInfo, TagReport, UhfError,
 };
 
-pub struct UhfRfid<T: RfidTransport> {
-    transport: T,
+/// Frames answering a command, and the unparsed bytes left when none did
+type Exchange = (Vec<Frame>, Vec<u8>);
+
+// The clock parameter defaults to `StdClock` when it is available. The struct is
+// declared through a macro so the fields are listed once for both configurations.
+macro_rules! declare_reader {
+    ($($clock_default:tt)*) => {
+        pub struct UhfRfid<T: RfidTransport, C: Clock $($clock_default)*> {
+            transport: T,
+            clock: C,
+            /// Whether the module is known to be asleep and needs a wake byte
+            asleep: bool,
+            /// Inactivity period after which the module puts itself to sleep
+            auto_sleep: Option<Duration>,
+            /// Time of the last exchange with the module
+            last_activity: Duration,
+            /// How long to wait for a response when no per-command deadline is set
+            default_timeout: Duration,
+            /// Per-command response deadlines
+            response_timeouts: Vec<(u8, Duration)>,
+            /// Frames received while waiting for the response to another command
+            notifications: VecDeque<Vec<u8>>,
+            /// Inventory counters, while collection is enabled
+            stats: Option<InventoryStats>,
+            /// Channel last set or read, unknown after hopping is enabled
+            channel: Option<u8>,
+            /// Checks applied to irreversible commands
+            guard_mode: GuardMode,
+            /// Confirmation for the next irreversible command
+            confirmation: Option<Confirmation>,
+            /// Whether every frame sent and received is dumped
+            frame_dump: bool,
+            /// Whether passwords are logged in plain text
+            log_passwords: bool,
+            /// Received frames dropped for a bad length, checksum or end marker
+            rejected_frames: usize,
+        }
+    };
 }
 
+#[cfg(feature = "std")]
+declare_reader!(= StdClock);
+#[cfg(not(feature = "std"))]
+declare_reader!();
+
+#[cfg(feature = "std")]
 impl<T: RfidTransport> UhfRfid<T> {
+    /// Create a new RFID reader instance with the given transport
+    pub fn new(transport: T) -> Self {
+        Self::with_clock(transport, StdClock::new())
+    }
+}
+
+impl<T: RfidTransport, C: Clock> UhfRfid<T, C> {
     // Protocol constants
-    const HEADER: u8 = 0xBB;
-    const END: u8 = 0x7E;
-    const CMD_TYPE: u8 = 0x00;
+    const HEADER: u8 = frame::HEADER;
     const RESP_TYPE_NOTIFICATION: u8 = 0x01;
-    const RESP_TYPE_TAG: u8 = 0x02;
+    const RESP_TYPE_TAG: u8 = frame::RESP_TYPE_TAG;
 
     // Command codes
     const GET_FIRMWARE: u8 = 0x03;
@@ -39,40 +103,289 @@ impl<T: RfidTransport> UhfRfid<T> {
     const SET_CHANNEL: u8 = 0xAB;
     const SET_AUTO_FREQ_HOP: u8 = 0xAD;
     const SET_CONTINUOUS_CARRIER: u8 = 0xB0;
-    const READ_TAG_DATA: u8 = 0x39;
-    const WRITE_TAG_DATA: u8 = 0x49;
-    const LOCK_TAG: u8 = 0x82;
-    const KILL_TAG: u8 = 0x65;
+    const READ_TAG_DATA: u8 = frame::READ_TAG_DATA;
+    const WRITE_TAG_DATA: u8 = frame::WRITE_TAG_DATA;
+
+    // Module error codes for tag memory access. Read and write errors carry the
+    // tag's EPC Gen2 error code in the low nibble.
+    const ACCESS_FAIL: u8 = 0x16;
+    const READ_ERROR: u8 = 0xA0;
+    const WRITE_ERROR: u8 = 0xB0;
+    const TAG_MEMORY_OVERRUN: u8 = 0x03;
+    const TAG_MEMORY_LOCKED: u8 = 0x04;
+
+    /// Words read per command while dumping a tag
+    const DUMP_CHUNK_WORDS: usize = 8;
+    /// Largest write accepted by `write_tag_data` (64 bytes)
+    const MAX_WRITE_WORDS: usize = 32;
+    /// Inventory rounds run to check the field before an irreversible command
+    const GUARD_INVENTORY_ROUNDS: u16 = 10;
+    /// Reserved bank word addresses of the kill and access passwords
+    const KILL_PASSWORD_WORD: u8 = 0;
+    const ACCESS_PASSWORD_WORD: u8 = 2;
+    const LOCK_TAG: u8 = frame::LOCK_TAG;
+    const KILL_TAG: u8 = frame::KILL_TAG;
     const INVENTORY_BUFFER: u8 = 0x18;
     const GET_BUFFER_DATA: u8 = 0x29;
     const CLEAR_BUFFER: u8 = 0x2A;
     const GET_RF_LINK_PROFILE: u8 = 0x6A;
     const SET_RF_LINK_PROFILE: u8 = 0x69;
-    const BLOCK_PERMALOCK: u8 = 0xD3;
-    const NXP_CHANGE_CONFIG: u8 = 0xE0;
-    const NXP_READ_PROTECT: u8 = 0xE1;
-    const NXP_RESET_READ_PROTECT: u8 = 0xE2;
-    const NXP_CHANGE_EAS: u8 = 0xE3;
+    const BLOCK_PERMALOCK: u8 = frame::BLOCK_PERMALOCK;
+    /// Read/Lock field of BlockPermalock selecting the status readback
+    const PERMALOCK_READ: u8 = 0x00;
+    /// Read/Lock field of BlockPermalock selecting the lock
+    const PERMALOCK_LOCK: u8 = 0x01;
+    /// Command code of the response to a BlockPermalock lock
+    const BLOCK_PERMALOCK_LOCKED: u8 = 0xD4;
+    const NXP_CHANGE_CONFIG: u8 = frame::NXP_CHANGE_CONFIG;
+    const NXP_READ_PROTECT: u8 = frame::NXP_READ_PROTECT;
+    const NXP_RESET_READ_PROTECT: u8 = frame::NXP_RESET_READ_PROTECT;
+    const NXP_CHANGE_EAS: u8 = frame::NXP_CHANGE_EAS;
     const NXP_EAS_ALARM: u8 = 0xE4;
-    const IMPINJ_MONZA_QT: u8 = 0xE5;
-    const SET_READER_SENSITIVITY: u8 = 0xF0;
-    const GET_READER_SENSITIVITY: u8 = 0xF1;
+    /// Error code of EAS_Alarm when no tag answered
+    const EAS_ALARM_FAIL: u8 = 0x1D;
+    const IMPINJ_MONZA_QT: u8 = frame::IMPINJ_MONZA_QT;
+    // Read/Write field of the QT command
+    const QT_READ: u8 = 0x00;
+    const QT_WRITE: u8 = 0x01;
+    const SET_DEMODULATOR_PARAMS: u8 = 0xF0;
+    const GET_DEMODULATOR_PARAMS: u8 = 0xF1;
+    const SLEEP: u8 = 0x17;
+    const SET_AUTO_SLEEP: u8 = 0x1D;
+    const SET_IDLE_MODE: u8 = 0x04;
+    const SCAN_JAMMER: u8 = 0xF2;
+    const SCAN_RSSI: u8 = 0xF3;
+    const IO_CONTROL: u8 = 0x1A;
+
+    // Any byte wakes the module up; it is discarded rather than parsed
+    const WAKE_BYTE: u8 = 0x00;
+    // Time for the module to reload the M100 firmware after waking up
+    const WAKE_DELAY: Duration = Duration::from_millis(100);
+
+    // Time for the module to switch baud rate before talking to it again
+    const BAUD_SWITCH_DELAY: Duration = Duration::from_millis(50);
+
+    // Response deadlines; channel scans sweep the whole band before answering
+    const DEFAULT_RESPONSE_TIMEOUT: Duration = Duration::from_millis(500);
+    const SCAN_RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+    // Pause before reading again when a transport returns without data or waiting
+    const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(1);
+    // Bytes kept while waiting for a response before giving up on the stream
+    const MAX_PENDING_BYTES: usize = 1024;
+    // Unrelated frames kept for take_notifications, oldest dropped first
+    const MAX_QUEUED_NOTIFICATIONS: usize = 32;
+
+    // Operation types for IO_CONTROL
+    const IO_SET_DIRECTION: u8 = 0x00;
+    const IO_SET_LEVEL: u8 = 0x01;
+    const IO_READ_LEVEL: u8 = 0x02;
+
+    // Module information types for GET_FIRMWARE
+    const INFO_HARDWARE: u8 = 0x00;
+    const INFO_FIRMWARE: u8 = 0x01;
+    const INFO_MANUFACTURER: u8 = 0x02;
+
+    /// Create a new RFID reader instance with the given transport and clock
+    pub fn with_clock(transport: T, clock: C) -> Self {
+        let last_activity = clock.now();
+        Self {
+            transport,
+            clock,
+            asleep: false,
+            auto_sleep: None,
+            last_activity,
+            default_timeout: Self::DEFAULT_RESPONSE_TIMEOUT,
+            response_timeouts: vec![
+                (Self::SCAN_JAMMER, Self::SCAN_RESPONSE_TIMEOUT),
+                (Self::SCAN_RSSI, Self::SCAN_RESPONSE_TIMEOUT),
+            ],
+            notifications: VecDeque::new(),
+            stats: None,
+            channel: None,
+            guard_mode: GuardMode::Off,
+            confirmation: None,
+            frame_dump: false,
+            log_passwords: false,
+            rejected_frames: 0,
+        }
+    }
 
-    /// Create a new RFID reader instance with the given transport
-    pub fn new(transport: T) -> Self {
-        Self { transport }
+    /// Give back the transport
+    pub fn into_transport(self) -> T {
+        self.transport
+    }
+
+    /// Set how long to wait for responses to commands without their own deadline
+    pub fn set_default_response_timeout(&mut self, timeout: Duration) {
+        self.default_timeout = timeout;
+    }
+
+    /// Set how long to wait for the response to a specific command code
+    ///
+    /// Responses are returned as soon as they are complete, so the deadline
+    /// only matters when the module is slow or does not answer at all.
+    pub fn set_response_timeout(&mut self, command: u8, timeout: Duration) {
+        match self.response_timeouts.iter_mut().find(|(code, _)| *code == command) {
+            Some(entry) => entry.1 = timeout,
+            None => self.response_timeouts.push((command, timeout)),
+        }
+    }
+
+    /// Dump every frame sent to and received from the module
+    ///
+    /// Frames are hex dumps at trace level, as `tracing` events with the `tracing`
+    /// feature and `log` records otherwise. Passwords are masked, see
+    /// [`set_log_passwords`](Self::set_log_passwords).
+    pub fn set_frame_dump(&mut self, enabled: bool) {
+        self.frame_dump = enabled;
+    }
+
+    /// Log passwords in plain text
+    ///
+    /// Access and kill passwords, and data written to or read from the Reserved bank,
+    /// are masked in log messages, spans and frame dumps by default. For lab use,
+    /// where the traffic needs to be seen as sent.
+    pub fn set_log_passwords(&mut self, enabled: bool) {
+        self.log_passwords = enabled;
+    }
+
+    /// Number of received frames dropped so far for a bad length, checksum or end marker
+    ///
+    /// Counts frames seen while waiting for command responses and during
+    /// `multiple_poll` and `poll_for_duration`; the allocation-free polling methods
+    /// count theirs in [`FrameBuffer::discarded_frames`].
+    pub fn rejected_frames(&self) -> usize {
+        self.rejected_frames
+    }
+
+    /// Take the frames that arrived while waiting for responses to other commands
+    ///
+    /// Tag notifications left over from an inventory or late responses end up here
+    /// instead of being mistaken for the answer to the next command. The
+    /// allocation-free methods taking a [`FrameBuffer`] drop such frames instead.
+    pub fn take_notifications(&mut self) -> Vec<Vec<u8>> {
+        self.notifications.drain(..).collect()
+    }
+
+    /// Start collecting [`InventoryStats`] over all following inventory calls
+    ///
+    /// Counters collected so far are reset. Tag reads are attributed to the channel
+    /// last set or read with [`set_channel`](Self::set_channel) or
+    /// [`get_channel`](Self::get_channel); while hopping is enabled they are not
+    /// counted per channel. Collection keeps a set of EPCs, so the `*_into` polling
+    /// methods allocate while it is enabled.
+    pub fn enable_inventory_stats(&mut self) {
+        self.stats = Some(InventoryStats::default());
+    }
+
+    /// Stop collecting inventory statistics and return the final counters
+    pub fn disable_inventory_stats(&mut self) -> Option<InventoryStats> {
+        self.stats.take()
+    }
+
+    /// Counters collected since collection was enabled or the stats were last taken
+    pub fn inventory_stats(&self) -> Option<&InventoryStats> {
+        self.stats.as_ref()
+    }
+
+    /// Take the counters collected so far and keep collecting from zero
+    pub fn take_inventory_stats(&mut self) -> Option<InventoryStats> {
+        self.stats.as_mut().map(core::mem::take)
+    }
+
+    /// Run inventory calls in `f` and return their result with the counters they produced
+    ///
+    /// Works whether or not collection is enabled; if it is, the counters are also
+    /// added to the running totals. Per-channel counts follow the same rules as for
+    /// [`enable_inventory_stats`](Self::enable_inventory_stats).
+    pub fn with_inventory_stats<R>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<R, UhfError>,
+    ) -> Result<(R, InventoryStats), UhfError> {
+        let outer = self.stats.replace(InventoryStats::default());
+        let result = f(self);
+        let stats = core::mem::replace(&mut self.stats, outer).unwrap_or_default();
+        if let Some(outer) = self.stats.as_mut() {
+            outer.merge(&stats);
+        }
+        result.map(|value| (value, stats))
+    }
+
+    /// Set the checks applied to irreversible commands
+    ///
+    /// In [`GuardMode::Guarded`] and [`GuardMode::DryRun`], each irreversible command
+    /// needs a [`confirm`](Self::confirm)ation. The command first runs a short
+    /// inventory with the current select settings and is refused unless exactly one
+    /// tag answers and it matches the confirmation. In [`GuardMode::DryRun`] a command
+    /// that passes the checks is not sent and fails with [`UhfError::DryRun`].
+    pub fn set_guard_mode(&mut self, mode: GuardMode) {
+        self.guard_mode = mode;
+        self.confirmation = None;
+    }
+
+    /// Checks currently applied to irreversible commands
+    pub fn guard_mode(&self) -> GuardMode {
+        self.guard_mode
+    }
+
+    /// Confirm the next irreversible command for the named tag
+    ///
+    /// The confirmation is used up by the next irreversible command, whether or not
+    /// its checks pass.
+    pub fn confirm(&mut self, confirmation: Confirmation) {
+        self.confirmation = Some(confirmation);
     }
 
     /// Get firmware version
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn get_firmware_version(&mut self) -> Result<String, UhfError> {
-        self.exec(&Self::create_command(Self::GET_FIRMWARE, &[0x01]))
-            .and_then(|response| Self::parse_firmware_version(&response))
+        self.get_info_string(Self::INFO_FIRMWARE)
+    }
+
+    /// Get hardware version
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn get_hardware_version(&mut self) -> Result<String, UhfError> {
+        self.get_info_string(Self::INFO_HARDWARE)
+    }
+
+    /// Get manufacturer string
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn get_manufacturer(&mut self) -> Result<String, UhfError> {
+        self.get_info_string(Self::INFO_MANUFACTURER)
+    }
+
+    /// Get hardware version, firmware version and manufacturer in one call
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn get_module_info(&mut self) -> Result<ModuleInfo, UhfError> {
+        Ok(ModuleInfo {
+            hardware_version: self.get_hardware_version()?,
+            firmware_version: self.get_firmware_version()?,
+            manufacturer: self.get_manufacturer()?,
+        })
+    }
+
+    fn get_info_string(&mut self, info_type: u8) -> Result<String, UhfError> {
+        self.exec(&Self::create_command(Self::GET_FIRMWARE, &[info_type]))
+            .and_then(|response| Self::parse_module_info(&response, info_type))
     }
 
     /// Poll for a single RFID tag
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(tags = tracing::field::Empty))
+    )]
     pub fn single_poll(&mut self) -> Result<Option<TagInfo>, UhfError> {
-        self.exec(&Self::create_command(Self::SINGLE_POLL, &[]))
-            .and_then(|response| Self::parse_tag(&response))
+        let start = self.clock.now();
+        let response = self.exec(&Self::create_command(Self::SINGLE_POLL, &[]))?;
+        let tag = Self::parse_tag(&response);
+
+        if let Ok(Some(tag)) = &tag {
+            self.record_read(&tag.epc, tag.rssi);
+        }
+        let ended = usize::from(Self::is_inventory_end(&response));
+        self.record_inventory(1, start, usize::from(tag.is_err()), ended);
+        trace::record_tags(usize::from(matches!(tag, Ok(Some(_)))));
+        tag
     }
 
     /// Poll for multiple RFID tags with a callback for each tag
@@ -83,6 +396,10 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// # Returns
     /// Total number of tags discovered across all rounds
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(tags = tracing::field::Empty))
+    )]
     pub fn multiple_poll_with_callback<F>(&mut self, rounds: u16, mut callback: F) -> Result<usize, UhfError>
     where
         F: FnMut(TagInfo),
@@ -93,75 +410,81 @@ impl<T: RfidTransport> UhfRfid<T> {
 
         let rounds_msb = (rounds >> 8) as u8;
         let rounds_lsb = (rounds & 0xFF) as u8;
+        let params = [0x22, rounds_msb, rounds_lsb];
+        let cmd = Self::create_command(Self::MULTIPLE_POLL, &params);
 
+        let span = trace::command_span(Self::MULTIPLE_POLL, &params, self.log_passwords);
+        let call_start = self.clock.now();
+        self.wake_if_asleep()?;
         self.transport
             .clear_input()
             .map_err(|e| UhfError::Transport(format!("{:?}", e)))?;
         self.transport
-            .write(&Self::create_command(
-                Self::MULTIPLE_POLL,
-                &[0x22, rounds_msb, rounds_lsb],
-            ))
+            .write(&cmd)
             .map_err(|e| UhfError::Transport(format!("{:?}", e)))?;
-        std::thread::sleep(Duration::from_millis(100));
+        trace::dump(self.frame_dump, "tx", self.redacted(&cmd));
+        self.clock.delay(Duration::from_millis(100));
 
         let mut tag_count = 0;
-        let start = Instant::now();
+        let mut discarded = 0;
+        let mut ended = false;
+        let rejected_before = self.rejected_frames;
+        let start = self.clock.now();
         let max_wait = Duration::from_secs(3);
         let mut buffer = Vec::new();
 
-        loop {
+        'receive: loop {
             let mut temp_buf = [0u8; 256];
 
             match self.transport.read(&mut temp_buf, 50) {
                 Ok(bytes_read) if bytes_read > 0 => {
                     buffer.extend_from_slice(&temp_buf[..bytes_read]);
 
-                    while let Some(frame_end) = buffer.iter().position(|&b| b == Self::END) {
-                        if let Some(frame_start) = buffer[..frame_end].iter().rposition(|&b| b == Self::HEADER) {
-                            let frame = &buffer[frame_start..=frame_end];
-
-                            if frame.len() >= 8
-                                && frame[1] == Self::RESP_TYPE_NOTIFICATION
-                                && frame[2] == 0xFF
-                                && frame[5] == 0x15
-                            {
-                                buffer.drain(..=frame_end);
-                                return Ok(tag_count);
-                            }
+                    while let Some(frame) =
+                        Self::take_frame(&mut buffer, &mut self.rejected_frames)
+                    {
+                        let frame = frame.as_bytes();
+                        trace::dump(self.frame_dump, "rx", self.redacted(frame));
 
-                            match Self::parse_tag(frame) {
-                                Ok(Some(tag)) => {
-                                    callback(tag);
-                                    tag_count += 1;
-                                }
-                                Ok(None) => {}
-                                Err(e) => {
-                                    warn!("Failed to parse frame: {:?}", e);
-                                }
-                            }
+                        if Self::is_inventory_end(frame) {
+                            trace::record_response(&span, frame);
+                            ended = true;
+                            break 'receive;
+                        }
 
-                            buffer.drain(..=frame_end);
-                        } else {
-                            buffer.drain(..=frame_end);
+                        match Self::parse_tag(frame) {
+                            Ok(Some(tag)) => {
+                                self.record_read(&tag.epc, tag.rssi);
+                                callback(tag);
+                                tag_count += 1;
+                            }
+                            Ok(None) => {}
+                            Err(e) => {
+                                warn!("Failed to parse frame: {:?}", e);
+                                discarded += 1;
+                            }
                         }
                     }
                 }
                 Ok(_) => {
-                    if start.elapsed() > max_wait {
+                    if self.elapsed_since(start) > max_wait {
                         break;
                     }
-                    std::thread::sleep(Duration::from_millis(50));
+                    self.clock.delay(Duration::from_millis(50));
                 }
                 Err(_) => {
-                    if start.elapsed() > max_wait {
+                    if self.elapsed_since(start) > max_wait {
                         break;
                     }
-                    std::thread::sleep(Duration::from_millis(50));
+                    self.clock.delay(Duration::from_millis(50));
                 }
             }
         }
 
+        discarded += self.rejected_frames - rejected_before;
+        self.record_inventory(rounds, call_start, discarded, usize::from(ended));
+        trace::record_duration(&span, self.elapsed_since(call_start));
+        trace::record_tags(tag_count);
         Ok(tag_count)
     }
 
@@ -172,9 +495,271 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// # Returns
     /// Vector of all tags discovered across all rounds
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(tags = tracing::field::Empty))
+    )]
     pub fn multiple_poll(&mut self, rounds: u16) -> Result<Vec<TagInfo>, UhfError> {
         let mut tags = Vec::new();
         self.multiple_poll_with_callback(rounds, |tag| tags.push(tag))?;
+        trace::record_tags(tags.len());
+        Ok(tags)
+    }
+
+    /// Poll for tags and filter them by signal strength
+    ///
+    /// Runs `rounds` inventory rounds and reduces the reads with
+    /// [`InventoryOptions::apply`], e.g. to react only to the tag closest to the
+    /// antenna with `strongest: Some(1)`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(tags = tracing::field::Empty))
+    )]
+    pub fn inventory(
+        &mut self,
+        rounds: u16,
+        options: &InventoryOptions,
+    ) -> Result<Vec<TagInfo>, UhfError> {
+        let mut reads = Vec::new();
+        self.multiple_poll_with_callback(rounds, |tag| reads.push(tag))?;
+        let tags = options.apply(reads);
+        trace::record_tags(tags.len());
+        Ok(tags)
+    }
+
+    /// Poll for a single RFID tag without heap allocation
+    ///
+    /// The response is received into `rx` and the returned tag borrows from it.
+    /// A 64-byte buffer is enough for tags with EPCs of up to 52 bytes. Unrelated
+    /// frames received before the response are dropped, not kept for
+    /// [`take_notifications`](Self::take_notifications).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(tags = tracing::field::Empty))
+    )]
+    pub fn single_poll_into<'b, const N: usize>(
+        &mut self,
+        rx: &'b mut FrameBuffer<N>,
+    ) -> Result<Option<TagRef<'b>>, UhfError> {
+        let start = self.clock.now();
+        let discarded_before = rx.discarded_frames();
+        let range = self.exec_frame(Self::SINGLE_POLL, &[], rx)?;
+        let discarded = rx.discarded_frames() - discarded_before;
+
+        let frame = rx.frame_at(range);
+        let tag = frame.tag();
+        if let Some(tag) = &tag {
+            self.record_tag_ref(tag);
+        }
+        let ended = usize::from(frame.error_code() == Some(0x15));
+        self.record_inventory(1, start, discarded, ended);
+        trace::record_tags(usize::from(tag.is_some()));
+        Ok(tag)
+    }
+
+    /// Poll for multiple RFID tags without heap allocation
+    ///
+    /// Like [`multiple_poll_with_callback`](Self::multiple_poll_with_callback), but
+    /// frames are parsed in place in `rx` and handed to the callback as borrowed tags.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(tags = tracing::field::Empty))
+    )]
+    pub fn multiple_poll_into<const N: usize, F>(
+        &mut self,
+        rounds: u16,
+        rx: &mut FrameBuffer<N>,
+        mut callback: F,
+    ) -> Result<usize, UhfError>
+    where
+        F: FnMut(TagRef<'_>),
+    {
+        if rounds == 0 {
+            return Err(UhfError::InvalidParameter("Poll rounds must be at least 1".into()));
+        }
+
+        let [rounds_msb, rounds_lsb] = rounds.to_be_bytes();
+        let params = [0x22, rounds_msb, rounds_lsb];
+        let span = trace::command_span(Self::MULTIPLE_POLL, &params, self.log_passwords);
+        let call_start = self.clock.now();
+        let discarded_before = rx.discarded_frames();
+        rx.clear();
+        self.send_command(Self::MULTIPLE_POLL, &params)?;
+
+        let mut tag_count = 0;
+        let mut ended = false;
+        let start = self.clock.now();
+        let max_wait = Duration::from_secs(3);
+
+        'receive: loop {
+            while let Some(frame) = rx.next_frame() {
+                trace::dump(self.frame_dump, "rx", self.redacted(frame.as_bytes()));
+                if frame.error_code() == Some(0x15) {
+                    trace::record_response(&span, frame.as_bytes());
+                    ended = true;
+                    break 'receive;
+                }
+                if let Some(tag) = frame.tag() {
+                    self.record_tag_ref(&tag);
+                    callback(tag);
+                    tag_count += 1;
+                }
+            }
+
+            match rx.fill_from(&mut self.transport, 50) {
+                Ok(bytes_read) if bytes_read > 0 => {}
+                _ => {
+                    if self.elapsed_since(start) > max_wait {
+                        break;
+                    }
+                    self.clock.delay(Duration::from_millis(50));
+                }
+            }
+        }
+
+        let discarded = rx.discarded_frames() - discarded_before;
+        self.record_inventory(rounds, call_start, discarded, usize::from(ended));
+        trace::record_duration(&span, self.elapsed_since(call_start));
+        trace::record_tags(tag_count);
+        Ok(tag_count)
+    }
+
+    /// Run an inventory that retunes Q between `multiple_poll` bursts
+    ///
+    /// Session, target and Sel are kept from the current query parameters. Q is set
+    /// to `config.initial_q` and adjusted after every burst as described for
+    /// [`AdaptiveQ`]; the last chosen Q stays configured afterwards.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(tags = tracing::field::Empty))
+    )]
+    pub fn adaptive_inventory(&mut self, config: &AdaptiveQConfig) -> Result<AdaptiveInventory, UhfError> {
+        let mut adaptive = AdaptiveQ::new(*config)?;
+        let mut params = self.get_query_param()?;
+        params.q = adaptive.q();
+        self.set_query_param(&params)?;
+
+        let mut tags: Vec<TagInfo> = Vec::new();
+        let mut decisions = Vec::with_capacity(config.bursts as usize);
+
+        for _ in 0..config.bursts {
+            let start = self.clock.now();
+            let mut burst_tags: Vec<TagInfo> = Vec::new();
+            let reads = self.multiple_poll_with_callback(config.rounds_per_burst, |tag| {
+                if !burst_tags.contains(&tag) {
+                    burst_tags.push(tag);
+                }
+            })?;
+
+            let decision = adaptive.observe(reads, burst_tags.len(), self.elapsed_since(start));
+            debug!("Adaptive Q: {:?}", decision);
+
+            for tag in burst_tags {
+                match tags.iter_mut().find(|known| **known == tag) {
+                    Some(known) => known.rssi = tag.rssi,
+                    None => tags.push(tag),
+                }
+            }
+
+            if decision.next_q != params.q {
+                params.q = decision.next_q;
+                self.set_query_param(&params)?;
+            }
+            decisions.push(decision);
+        }
+
+        trace::record_tags(tags.len());
+        Ok(AdaptiveInventory { tags, decisions })
+    }
+
+    /// Run an inventory that drives the session flags according to `strategy`
+    ///
+    /// The query parameters are switched to the strategy's session and target A with
+    /// Sel set to all tags; Q is kept. Strategies that reset flags configure a Select
+    /// with an empty mask, which matches every tag and moves its flag to A, and enable
+    /// it for the first burst only. Bursts of `rounds_per_burst` rounds run until the
+    /// strategy is quiet or `max_bursts` is reached. A burst is quiet when no tag
+    /// answered at all; tags read again still count as activity. Select mode is left
+    /// disabled.
+    ///
+    /// # Returns
+    /// Distinct tags read over all bursts, with the RSSI of the latest read
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(tags = tracing::field::Empty))
+    )]
+    pub fn run_inventory(
+        &mut self,
+        strategy: InventoryStrategy,
+        rounds_per_burst: u16,
+        max_bursts: u16,
+    ) -> Result<Vec<TagInfo>, UhfError> {
+        if rounds_per_burst == 0 || max_bursts == 0 {
+            return Err(UhfError::InvalidParameter(
+                "Rounds per burst and burst count must be non-zero".into(),
+            ));
+        }
+
+        let mut params = self.get_query_param()?;
+        params.sel = QuerySel::All;
+        params.session = strategy.session();
+        params.target = QueryTarget::A;
+        self.set_query_param(&params)?;
+
+        if strategy.resets_flags() {
+            self.set_select_param(&SelectParams {
+                target: strategy.session().into(),
+                action: SelectAction::Action0,
+                mem_bank: MemoryBank::Epc,
+                pointer: 0,
+                mask: Vec::new(),
+                truncate: false,
+            })?;
+            self.set_select_mode(SelectMode::Always)?;
+        } else {
+            self.set_select_mode(SelectMode::Disabled)?;
+        }
+
+        let mut tags: Vec<TagInfo> = Vec::new();
+        let mut quiet_sides = 0;
+
+        for burst in 0..max_bursts {
+            let mut new_tags = 0;
+            let reads = self.multiple_poll_with_callback(rounds_per_burst, |tag| {
+                match tags.iter_mut().find(|known| **known == tag) {
+                    Some(known) => known.rssi = tag.rssi,
+                    None => {
+                        tags.push(tag);
+                        new_tags += 1;
+                    }
+                }
+            })?;
+            debug!(
+                "Inventory burst {} on target {:?}: {} reads, {} new tags",
+                burst, params.target, reads, new_tags
+            );
+
+            if burst == 0 && strategy.resets_flags() {
+                // Selecting again would put the tags just read back to A
+                self.set_select_mode(SelectMode::Disabled)?;
+            }
+
+            if reads > 0 {
+                quiet_sides = 0;
+                continue;
+            }
+
+            quiet_sides += 1;
+            match strategy {
+                InventoryStrategy::DualTarget { .. } if quiet_sides < 2 => {
+                    params.target = params.target.flipped();
+                    self.set_query_param(&params)?;
+                }
+                _ => break,
+            }
+        }
+
+        trace::record_tags(tags.len());
         Ok(tags)
     }
 
@@ -188,9 +773,14 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// # Returns
     /// Vector of all tags discovered during the timeout period
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(tags = tracing::field::Empty))
+    )]
     pub fn poll_for_duration(&mut self, timeout: Duration) -> Result<Vec<TagInfo>, UhfError> {
         let mut tags = Vec::new();
         self.poll_for_duration_with_callback(timeout, |tag| tags.push(tag))?;
+        trace::record_tags(tags.len());
         Ok(tags)
     }
 
@@ -199,12 +789,20 @@ impl<T: RfidTransport> UhfRfid<T> {
     /// This starts continuous polling (0xFFFF rounds) and calls the callback
     /// for each tag discovered until the timeout expires, then stops polling.
     ///
+    /// Inventory statistics record the reads, time, discarded frames and end
+    /// notifications, but not `rounds`: the module is asked for 0xFFFF rounds at
+    /// a time and stopped early, so the number actually run is unknown.
+    ///
     /// # Arguments
     /// * `timeout` - How long to poll for tags
     /// * `callback` - Function called for each discovered tag
     ///
     /// # Returns
     /// Total number of tags discovered
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(tags = tracing::field::Empty))
+    )]
     pub fn poll_for_duration_with_callback<F>(
         &mut self,
         timeout: Duration,
@@ -214,89 +812,93 @@ impl<T: RfidTransport> UhfRfid<T> {
         F: FnMut(TagInfo),
     {
         // Start continuous polling with max count
+        let params = [0x22, 0xFF, 0xFF]; // 0xFFFF = 65535 rounds (continuous)
+        let cmd = Self::create_command(Self::MULTIPLE_POLL, &params);
+        let span = trace::command_span(Self::MULTIPLE_POLL, &params, self.log_passwords);
+        self.wake_if_asleep()?;
         self.transport
             .clear_input()
             .map_err(|e| UhfError::Transport(format!("{:?}", e)))?;
         self.transport
-            .write(&Self::create_command(
-                Self::MULTIPLE_POLL,
-                &[0x22, 0xFF, 0xFF], // 0xFFFF = 65535 rounds (continuous)
-            ))
+            .write(&cmd)
             .map_err(|e| UhfError::Transport(format!("{:?}", e)))?;
+        trace::dump(self.frame_dump, "tx", self.redacted(&cmd));
 
         let mut tag_count = 0;
-        let start = Instant::now();
+        let mut discarded = 0;
+        let mut end_notifications = 0;
+        let rejected_before = self.rejected_frames;
+        let start = self.clock.now();
         let mut buffer = Vec::new();
 
         // Read tags until timeout
-        while start.elapsed() < timeout {
+        while self.elapsed_since(start) < timeout {
             let mut temp_buf = [0u8; 256];
 
             match self.transport.read(&mut temp_buf, 50) {
                 Ok(bytes_read) if bytes_read > 0 => {
                     buffer.extend_from_slice(&temp_buf[..bytes_read]);
 
-                    while let Some(frame_end) = buffer.iter().position(|&b| b == Self::END) {
-                        if let Some(frame_start) =
-                            buffer[..frame_end].iter().rposition(|&b| b == Self::HEADER)
-                        {
-                            let frame = &buffer[frame_start..=frame_end];
-
-                            // Check for end-of-poll notification - restart polling
-                            if frame.len() >= 8
-                                && frame[1] == Self::RESP_TYPE_NOTIFICATION
-                                && frame[2] == 0xFF
-                                && frame[5] == 0x15
-                            {
-                                buffer.drain(..=frame_end);
-                                // Restart polling if we still have time
-                                if start.elapsed() < timeout {
-                                    let _ = self.transport.write(&Self::create_command(
-                                        Self::MULTIPLE_POLL,
-                                        &[0x22, 0xFF, 0xFF],
-                                    ));
-                                }
-                                continue;
+                    while let Some(frame) =
+                        Self::take_frame(&mut buffer, &mut self.rejected_frames)
+                    {
+                        let frame = frame.as_bytes();
+                        trace::dump(self.frame_dump, "rx", self.redacted(frame));
+
+                        // Check for end-of-poll notification - restart polling
+                        if Self::is_inventory_end(frame) {
+                            end_notifications += 1;
+                            // Restart polling if we still have time
+                            if self.elapsed_since(start) < timeout {
+                                let _ = self.transport.write(&cmd);
+                                trace::dump(self.frame_dump, "tx", self.redacted(&cmd));
                             }
+                            continue;
+                        }
 
-                            match Self::parse_tag(frame) {
-                                Ok(Some(tag)) => {
-                                    callback(tag);
-                                    tag_count += 1;
-                                }
-                                Ok(None) => {}
-                                Err(e) => {
-                                    warn!("Failed to parse frame: {:?}", e);
-                                }
+                        match Self::parse_tag(frame) {
+                            Ok(Some(tag)) => {
+                                self.record_read(&tag.epc, tag.rssi);
+                                callback(tag);
+                                tag_count += 1;
+                            }
+                            Ok(None) => {}
+                            Err(e) => {
+                                warn!("Failed to parse frame: {:?}", e);
+                                discarded += 1;
                             }
-
-                            buffer.drain(..=frame_end);
-                        } else {
-                            buffer.drain(..=frame_end);
                         }
                     }
                 }
                 Ok(_) => {
-                    std::thread::sleep(Duration::from_millis(10));
+                    self.clock.delay(Duration::from_millis(10));
                 }
                 Err(_) => {
-                    std::thread::sleep(Duration::from_millis(10));
+                    self.clock.delay(Duration::from_millis(10));
                 }
             }
         }
 
         // Stop polling
-        let _ = self.transport.write(&Self::create_command(Self::STOP_MULTIPLE_POLL, &[]));
+        let stop = Self::create_command(Self::STOP_MULTIPLE_POLL, &[]);
+        let _ = self.transport.write(&stop);
+        trace::dump(self.frame_dump, "tx", self.redacted(&stop));
 
         // Drain any remaining responses
-        std::thread::sleep(Duration::from_millis(100));
+        self.clock.delay(Duration::from_millis(100));
         let mut drain_buf = [0u8; 256];
         while self.transport.read(&mut drain_buf, 50).unwrap_or(0) > 0 {}
 
+        // Stopped before the requested 0xFFFF rounds, so no round count to report
+        discarded += self.rejected_frames - rejected_before;
+        self.record_inventory(0, start, discarded, end_notifications);
+        trace::record_duration(&span, self.elapsed_since(start));
+        trace::record_tags(tag_count);
         Ok(tag_count)
     }
 
     /// Get current transmit power in dBm
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn get_tx_power(&mut self) -> Result<u16, UhfError> {
         let response = self.exec(&Self::create_command(Self::GET_TX_POWER, &[]))?;
 
@@ -316,6 +918,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     }
 
     /// Set transmit power (18-26 dBm valid range)
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn set_tx_power(&mut self, power_dbm: u16) -> Result<(), UhfError> {
         const MIN_POWER_DBM: u16 = 18;
         const MAX_POWER_DBM: u16 = 26;
@@ -379,6 +982,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// This sets the Select parameters and automatically enables Select mode (0x02).
     /// Use this to filter operations to specific tags based on EPC, TID, or other memory.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn set_select_param(&mut self, params: &SelectParams) -> Result<(), UhfError> {
         // Validate mask length (max 255 bits = 31.875 bytes, protocol uses bytes)
         if params.mask.len() > 32 {
@@ -424,6 +1028,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     }
 
     /// Get current Select parameters
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn get_select_param(&mut self) -> Result<SelectParams, UhfError> {
         let response = self.exec(&Self::create_command(Self::GET_SELECT_PARAM, &[]))?;
 
@@ -469,7 +1074,7 @@ impl<T: RfidTransport> UhfRfid<T> {
         let truncate = response[11] == 0x80;
 
         // Calculate mask length in bytes
-        let mask_len_bytes = ((mask_len_bits + 7) / 8) as usize;
+        let mask_len_bytes = mask_len_bits.div_ceil(8) as usize;
 
         // Extract mask data
         let mask_start = 12;
@@ -496,6 +1101,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     /// - `Always` (0x00): Send Select command before every tag operation
     /// - `Disabled` (0x01): Do not send Select command
     /// - `NonPolling` (0x02): Send Select only before Read, Write, Lock, Kill (not polling)
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn set_select_mode(&mut self, mode: SelectMode) -> Result<(), UhfError> {
         let response = self.exec(&Self::create_command(Self::SET_SELECT_MODE, &[mode as u8]))?;
 
@@ -516,6 +1122,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     // ========================================================================
 
     /// Get current Query parameters
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn get_query_param(&mut self) -> Result<QueryParams, UhfError> {
         let response = self.exec(&Self::create_command(Self::GET_QUERY_PARAM, &[]))?;
 
@@ -535,6 +1142,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     /// Set Query parameters
     ///
     /// These parameters control the EPC Gen2 Query command used during inventory.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn set_query_param(&mut self, params: &QueryParams) -> Result<(), UhfError> {
         if params.q > 15 {
             return Err(UhfError::InvalidParameter(
@@ -558,6 +1166,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     }
 
     /// Get current operating region
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn get_region(&mut self) -> Result<Region, UhfError> {
         let response = self.exec(&Self::create_command(Self::GET_REGION, &[]))?;
 
@@ -579,6 +1188,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// This configures the frequency band the reader operates in.
     /// The region must match local regulations.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn set_region(&mut self, region: Region) -> Result<(), UhfError> {
         let response = self.exec(&Self::create_command(Self::SET_REGION, &[region as u8]))?;
 
@@ -588,6 +1198,7 @@ impl<T: RfidTransport> UhfRfid<T> {
             && response[2] == Self::SET_REGION
             && response[5] == 0x00
         {
+            self.channel = None;
             Ok(())
         } else {
             Err(UhfError::InvalidResponse("Failed to set region".into()))
@@ -595,6 +1206,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     }
 
     /// Get current operating channel index
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn get_channel(&mut self) -> Result<u8, UhfError> {
         let response = self.exec(&Self::create_command(Self::GET_CHANNEL, &[]))?;
 
@@ -605,6 +1217,7 @@ impl<T: RfidTransport> UhfRfid<T> {
             && response[3] == 0x00
             && response[4] == 0x01
         {
+            self.channel = Some(response[5]);
             Ok(response[5])
         } else {
             Err(UhfError::InvalidResponse("Failed to get channel".into()))
@@ -615,6 +1228,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// The valid channel range depends on the configured region.
     /// Use `Region::frequency_from_channel()` to calculate the actual frequency.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn set_channel(&mut self, channel: u8) -> Result<(), UhfError> {
         let response = self.exec(&Self::create_command(Self::SET_CHANNEL, &[channel]))?;
 
@@ -624,6 +1238,7 @@ impl<T: RfidTransport> UhfRfid<T> {
             && response[2] == Self::SET_CHANNEL
             && response[5] == 0x00
         {
+            self.channel = Some(channel);
             Ok(())
         } else {
             Err(UhfError::InvalidResponse("Failed to set channel".into()))
@@ -634,6 +1249,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// When enabled, the reader automatically hops between channels.
     /// When disabled, the reader uses a fixed channel set via `set_channel()`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn set_auto_freq_hop(&mut self, enabled: bool) -> Result<(), UhfError> {
         let param = if enabled { 0xFF } else { 0x00 };
         let response = self.exec(&Self::create_command(Self::SET_AUTO_FREQ_HOP, &[param]))?;
@@ -644,17 +1260,40 @@ impl<T: RfidTransport> UhfRfid<T> {
             && response[2] == Self::SET_AUTO_FREQ_HOP
             && response[5] == 0x00
         {
+            if enabled {
+                self.channel = None;
+            }
             Ok(())
         } else {
             Err(UhfError::InvalidResponse("Failed to set auto frequency hopping".into()))
         }
     }
 
-    /// Insert a channel into the frequency hopping table
+    /// Insert a single channel as the frequency hopping table
     ///
-    /// This adds a channel to the list of channels used during frequency hopping.
+    /// Shorthand for `insert_channels(&[channel])`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn insert_channel(&mut self, channel: u8) -> Result<(), UhfError> {
-        let response = self.exec(&Self::create_command(Self::INSERT_CHANNEL, &[channel]))?;
+        self.insert_channels(&[channel])
+    }
+
+    /// Replace the frequency hopping table with a list of channels
+    ///
+    /// While automatic frequency hopping is enabled, the reader randomly hops
+    /// between the channels in this list instead of its built-in table.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn insert_channels(&mut self, channels: &[u8]) -> Result<(), UhfError> {
+        if channels.is_empty() || channels.len() > u8::MAX as usize {
+            return Err(UhfError::InvalidParameter(
+                "Channel list must contain 1-255 channels".into(),
+            ));
+        }
+
+        let mut params = Vec::with_capacity(1 + channels.len());
+        params.push(channels.len() as u8);
+        params.extend_from_slice(channels);
+
+        let response = self.exec(&Self::create_command(Self::INSERT_CHANNEL, &params))?;
 
         if response.len() >= 7
             && response[0] == Self::HEADER
@@ -668,10 +1307,22 @@ impl<T: RfidTransport> UhfRfid<T> {
         }
     }
 
+    /// Program a custom frequency hopping plan
+    ///
+    /// Sets the plan's region, installs its channel list as the hopping table
+    /// and enables automatic frequency hopping.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn set_hopping_plan(&mut self, plan: &HoppingPlan) -> Result<(), UhfError> {
+        self.set_region(plan.region())?;
+        self.insert_channels(plan.channels())?;
+        self.set_auto_freq_hop(true)
+    }
+
     /// Set continuous carrier transmission
     ///
     /// When enabled, the reader transmits a continuous unmodulated carrier.
     /// This is primarily used for testing and regulatory compliance verification.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn set_continuous_carrier(&mut self, enabled: bool) -> Result<(), UhfError> {
         let param = if enabled { 0xFF } else { 0x00 };
         let response = self.exec(&Self::create_command(Self::SET_CONTINUOUS_CARRIER, &[param]))?;
@@ -688,33 +1339,248 @@ impl<T: RfidTransport> UhfRfid<T> {
         }
     }
 
-    /// Set communication baud rate
+    /// Set the module's communication baud rate
+    ///
+    /// The module switches immediately and does not answer, so this only sends
+    /// the command. The transport keeps its current rate; use
+    /// [`change_baud_rate`](Self::change_baud_rate) to switch both ends.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn set_baud_rate(&mut self, baud_rate: BaudRate) -> Result<(), UhfError> {
+        self.send_command(Self::SET_BAUD_RATE, &baud_rate.to_bytes())?;
+        self.last_activity = self.clock.now();
+        Ok(())
+    }
+
+    /// Switch the module and the host transport to a new baud rate
+    ///
+    /// The link is checked with a firmware version query at the new rate. If that
+    /// fails, the transport goes back to its previous rate and an error is returned.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn change_baud_rate(&mut self, baud_rate: BaudRate) -> Result<(), UhfError> {
+        let Some(old_baud) = self.transport.host_baud() else {
+            return Err(UhfError::InvalidParameter(
+                "Transport does not support changing the baud rate".into(),
+            ));
+        };
+
+        self.set_baud_rate(baud_rate)?;
+        self.clock.delay(Self::BAUD_SWITCH_DELAY);
+        self.set_host_baud(baud_rate.bps())?;
+
+        if self.get_firmware_version().is_ok() {
+            debug!("Switched to {} bps", baud_rate.bps());
+            return Ok(());
+        }
+
+        warn!("No response at {} bps, falling back to {} bps", baud_rate.bps(), old_baud);
+        self.set_host_baud(old_baud)?;
+
+        match self.get_firmware_version() {
+            Ok(_) => Err(UhfError::InvalidResponse(format!(
+                "Reader did not respond at {} bps, staying at {} bps",
+                baud_rate.bps(),
+                old_baud
+            ))),
+            Err(_) => Err(UhfError::Transport(format!(
+                "Lost contact with reader while switching from {} to {} bps",
+                old_baud,
+                baud_rate.bps()
+            ))),
+        }
+    }
+
+    /// Find the baud rate the module is currently using
+    ///
+    /// Tries each candidate in order by switching the host transport and sending a
+    /// firmware version query. The transport is left at the detected rate.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn detect_baud_rate(&mut self, candidates: &[BaudRate]) -> Result<BaudRate, UhfError> {
+        for &candidate in candidates {
+            self.set_host_baud(candidate.bps())?;
+            if self.get_firmware_version().is_ok() {
+                debug!("Reader answered at {} bps", candidate.bps());
+                return Ok(candidate);
+            }
+        }
+
+        Err(UhfError::Transport("Reader did not respond at any candidate baud rate".into()))
+    }
+
+    fn set_host_baud(&mut self, baud_rate: u32) -> Result<(), UhfError> {
+        match self.transport.set_host_baud(baud_rate) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(UhfError::InvalidParameter(
+                "Transport does not support changing the baud rate".into(),
+            )),
+            Err(e) => Err(UhfError::Transport(format!("{:?}", e))),
+        }
+    }
+
+    // ========================================================================
+    // Power Management
+    // ========================================================================
+
+    /// Put the module into low-power sleep
+    ///
+    /// The module wakes up on the next byte it receives and discards that byte,
+    /// so the next command is automatically preceded by a wake byte.
+    ///
+    /// Waking up resets the M100 chip. Power, frequency, hopping, sleep time and
+    /// demodulator settings are restored, but Select mode and Select parameters
+    /// are lost and must be set again.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn sleep(&mut self) -> Result<(), UhfError> {
+        let response = self.exec(&Self::create_command(Self::SLEEP, &[]))?;
+
+        if response.len() >= 7
+            && response[0] == Self::HEADER
+            && response[1] == Self::RESP_TYPE_NOTIFICATION
+            && response[2] == Self::SLEEP
+            && response[5] == 0x00
+        {
+            self.asleep = true;
+            Ok(())
+        } else {
+            Err(UhfError::InvalidResponse("Failed to enter sleep mode".into()))
+        }
+    }
+
+    /// Set the inactivity time after which the module goes to sleep by itself
+    ///
+    /// # Arguments
+    /// * `minutes` - Idle minutes before sleeping (1-30), or 0 to disable auto-sleep
+    ///
+    /// While auto-sleep is enabled, commands sent after the idle period are
+    /// preceded by a wake byte, and a command that gets no response is retried once.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn set_auto_sleep(&mut self, minutes: u8) -> Result<(), UhfError> {
+        if minutes > 30 {
+            return Err(UhfError::InvalidParameter(
+                "Auto-sleep time must be 0-30 minutes".into(),
+            ));
+        }
+
+        let response = self.exec(&Self::create_command(Self::SET_AUTO_SLEEP, &[minutes]))?;
+
+        // The module echoes the configured time instead of a status byte
+        if response.len() >= 7
+            && response[0] == Self::HEADER
+            && response[1] == Self::RESP_TYPE_NOTIFICATION
+            && response[2] == Self::SET_AUTO_SLEEP
+            && response[5] == minutes
+        {
+            self.auto_sleep = match minutes {
+                0 => None,
+                m => Some(Duration::from_secs(m as u64 * 60)),
+            };
+            Ok(())
+        } else {
+            Err(UhfError::InvalidResponse("Failed to set auto-sleep time".into()))
+        }
+    }
+
+    /// Enter or leave IDLE mode
     ///
-    /// Supported baud rates:
-    /// - 0: 38400 bps
-    /// - 1: 115200 bps (default)
-    /// - 2: 9600 bps
+    /// In IDLE mode the analog and RF supplies are switched off while the module
+    /// keeps answering commands and retains its settings. The first tag operation
+    /// afterwards brings the RF section back up, possibly with a reduced success rate.
     ///
-    /// Note: After changing the baud rate, the transport must be reconfigured
-    /// to use the new rate before further communication is possible.
-    pub fn set_baud_rate(&mut self, rate_index: u8) -> Result<(), UhfError> {
-        if rate_index > 2 {
+    /// # Arguments
+    /// * `enabled` - true to enter IDLE mode now, false to leave it
+    /// * `auto_idle_minutes` - Idle minutes before entering IDLE mode automatically
+    ///   (0-30, 0 disables automatic IDLE)
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn set_idle_mode(&mut self, enabled: bool, auto_idle_minutes: u8) -> Result<(), UhfError> {
+        if auto_idle_minutes > 30 {
             return Err(UhfError::InvalidParameter(
-                "Baud rate index must be 0 (38400), 1 (115200), or 2 (9600)".into(),
+                "Auto-idle time must be 0-30 minutes".into(),
             ));
         }
 
-        let response = self.exec(&Self::create_command(Self::SET_BAUD_RATE, &[rate_index]))?;
+        // Second parameter byte is reserved and fixed at 0x01
+        let params = [if enabled { 0x01 } else { 0x00 }, 0x01, auto_idle_minutes];
+        let response = self.exec(&Self::create_command(Self::SET_IDLE_MODE, &params))?;
 
         if response.len() >= 7
             && response[0] == Self::HEADER
             && response[1] == Self::RESP_TYPE_NOTIFICATION
-            && response[2] == Self::SET_BAUD_RATE
+            && response[2] == Self::SET_IDLE_MODE
             && response[5] == 0x00
         {
             Ok(())
         } else {
-            Err(UhfError::InvalidResponse("Failed to set baud rate".into()))
+            Err(UhfError::InvalidResponse("Failed to set idle mode".into()))
+        }
+    }
+
+    /// Send a wake byte if the module is, or may have fallen, asleep
+    fn wake_if_asleep(&mut self) -> Result<(), UhfError> {
+        let dozed_off = self
+            .auto_sleep
+            .is_some_and(|idle| self.elapsed_since(self.last_activity) >= idle);
+
+        if self.asleep || dozed_off {
+            debug!("Waking module");
+            self.transport
+                .write(&[Self::WAKE_BYTE])
+                .map_err(|e| UhfError::Transport(format!("{:?}", e)))?;
+            self.clock.delay(Self::WAKE_DELAY);
+            self.asleep = false;
+        }
+
+        self.last_activity = self.clock.now();
+        Ok(())
+    }
+
+    // ========================================================================
+    // IO Port Control
+    // ========================================================================
+
+    /// Configure an IO port as input or output
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn configure_io(&mut self, pin: IoPin, direction: IoDirection) -> Result<(), UhfError> {
+        match self.io_control(Self::IO_SET_DIRECTION, pin, direction as u8)? {
+            0x01 => Ok(()),
+            _ => Err(UhfError::InvalidResponse("Failed to configure IO port".into())),
+        }
+    }
+
+    /// Drive an output IO port high or low
+    ///
+    /// The port must have been configured as an output with [`configure_io`](Self::configure_io).
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn write_io(&mut self, pin: IoPin, level: IoLevel) -> Result<(), UhfError> {
+        match self.io_control(Self::IO_SET_LEVEL, pin, level as u8)? {
+            0x01 => Ok(()),
+            _ => Err(UhfError::InvalidResponse("Failed to set IO port level".into())),
+        }
+    }
+
+    /// Read the current level of an IO port
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn read_io(&mut self, pin: IoPin) -> Result<IoLevel, UhfError> {
+        let level = self.io_control(Self::IO_READ_LEVEL, pin, 0x00)?;
+
+        IoLevel::try_from(level)
+            .map_err(|_| UhfError::InvalidResponse(format!("Unknown IO level: 0x{:02X}", level)))
+    }
+
+    /// Send an IO control operation and return the result byte
+    fn io_control(&mut self, operation: u8, pin: IoPin, value: u8) -> Result<u8, UhfError> {
+        let params = [operation, pin as u8, value];
+        let response = self.exec(&Self::create_command(Self::IO_CONTROL, &params))?;
+
+        // Response format: BB 01 1A 00 03 OP PIN RESULT checksum 7E
+        if response.len() >= 10
+            && response[0] == Self::HEADER
+            && response[1] == Self::RESP_TYPE_NOTIFICATION
+            && response[2] == Self::IO_CONTROL
+            && response[5] == operation
+            && response[6] == pin as u8
+        {
+            Ok(response[7])
+        } else {
+            Err(UhfError::InvalidResponse("Invalid IO control response".into()))
         }
     }
 
@@ -732,6 +1598,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// # Returns
     /// The raw bytes read from the tag memory
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn read_tag_data(
         &mut self,
         access_password: &[u8; 4],
@@ -745,6 +1612,20 @@ impl<T: RfidTransport> UhfRfid<T> {
             ));
         }
 
+        self.try_read_tag_data(access_password, mem_bank, word_ptr, word_count)?
+            .map_err(|code| {
+                UhfError::InvalidResponse(format!("Read failed with error code: 0x{:02X}", code))
+            })
+    }
+
+    /// Read tag memory, returning the module's error code if the tag could not be read
+    fn try_read_tag_data(
+        &mut self,
+        access_password: &[u8; 4],
+        mem_bank: MemoryBank,
+        word_ptr: u8,
+        word_count: u8,
+    ) -> Result<Result<Vec<u8>, u8>, UhfError> {
         let mut params = Vec::with_capacity(7);
         params.extend_from_slice(access_password);
         params.push(mem_bank as u8);
@@ -753,35 +1634,39 @@ impl<T: RfidTransport> UhfRfid<T> {
 
         let response = self.exec(&Self::create_command(Self::READ_TAG_DATA, &params))?;
 
-        // Response format: BB 02 39 00 LL [data...] checksum 7E
-        // or error: BB 01 39 00 01 EE checksum 7E
-        if response.len() >= 8
-            && response[0] == Self::HEADER
-            && response[2] == Self::READ_TAG_DATA
-        {
-            if response[1] == Self::RESP_TYPE_TAG {
-                // Success - extract data
-                let data_len = ((response[3] as usize) << 8) | (response[4] as usize);
-                let data_start = 5;
-                let data_end = data_start + data_len;
-
-                if response.len() >= data_end + 2 {
-                    Ok(response[data_start..data_end].to_vec())
-                } else {
-                    Err(UhfError::InvalidResponse("Response too short for data".into()))
-                }
-            } else if response[1] == Self::RESP_TYPE_NOTIFICATION {
-                // Error response
-                let error_code = if response.len() > 5 { response[5] } else { 0xFF };
-                Err(UhfError::InvalidResponse(format!(
-                    "Read failed with error code: 0x{:02X}",
-                    error_code
-                )))
-            } else {
-                Err(UhfError::InvalidResponse("Unexpected response type".into()))
-            }
-        } else {
-            Err(UhfError::InvalidResponse("Invalid read response".into()))
+        match Self::tag_access_payload(&response, Self::READ_TAG_DATA)? {
+            Ok(data) if data.len() == 2 * word_count as usize => Ok(Ok(data.to_vec())),
+            Ok(_) => Err(UhfError::InvalidResponse("Wrong data length in read response".into())),
+            Err(code) => Ok(Err(code)),
+        }
+    }
+
+    /// Payload of a tag access response after the tag's UL and PC+EPC, or the
+    /// module's error code
+    fn tag_access_payload(response: &[u8], command: u8) -> Result<Result<&[u8], u8>, UhfError> {
+        // Response format: BB 01 CMD PL PL UL [PC+EPC] [payload...] checksum 7E
+        // or error: BB 01 FF PL PL EE [UL PC+EPC] checksum 7E
+        if response.len() < 8
+            || response[0] != Self::HEADER
+            || response[1] != Self::RESP_TYPE_NOTIFICATION
+        {
+            return Err(UhfError::InvalidResponse("Invalid tag access response".into()));
+        }
+
+        let params = &response[5..response.len() - 2];
+        match response[2] {
+            frame::ERROR_COMMAND => Ok(Err(params[0])),
+            cmd if cmd == command => match params.get(1 + params[0] as usize..) {
+                Some(payload) => Ok(Ok(payload)),
+                None => Err(UhfError::InvalidResponse(format!(
+                    "Tag access response shorter than its PC+EPC: {:02X?}",
+                    response
+                ))),
+            },
+            _ => Err(UhfError::InvalidResponse(format!(
+                "Invalid tag access response: {:02X?}",
+                response
+            ))),
         }
     }
 
@@ -795,6 +1680,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// # Note
     /// Writing to EPC bank at word address 1 (PC + EPC) is common for re-encoding tags.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn write_tag_data(
         &mut self,
         access_password: &[u8; 4],
@@ -805,7 +1691,7 @@ impl<T: RfidTransport> UhfRfid<T> {
         if data.is_empty() {
             return Err(UhfError::InvalidParameter("Data cannot be empty".into()));
         }
-        if data.len() % 2 != 0 {
+        if !data.len().is_multiple_of(2) {
             return Err(UhfError::InvalidParameter(
                 "Data length must be even (word-aligned)".into(),
             ));
@@ -816,6 +1702,20 @@ impl<T: RfidTransport> UhfRfid<T> {
             ));
         }
 
+        self.try_write_tag_data(access_password, mem_bank, word_ptr, data)?
+            .map_err(|code| {
+                UhfError::InvalidResponse(format!("Write failed with error code: 0x{:02X}", code))
+            })
+    }
+
+    /// Write tag memory, returning the module's error code if the tag refused the write
+    fn try_write_tag_data(
+        &mut self,
+        access_password: &[u8; 4],
+        mem_bank: MemoryBank,
+        word_ptr: u8,
+        data: &[u8],
+    ) -> Result<Result<(), u8>, UhfError> {
         let word_count = (data.len() / 2) as u8;
 
         let mut params = Vec::with_capacity(7 + data.len());
@@ -827,23 +1727,265 @@ impl<T: RfidTransport> UhfRfid<T> {
 
         let response = self.exec(&Self::create_command(Self::WRITE_TAG_DATA, &params))?;
 
-        if response.len() >= 7
-            && response[0] == Self::HEADER
-            && response[1] == Self::RESP_TYPE_NOTIFICATION
-            && response[2] == Self::WRITE_TAG_DATA
-            && response[5] == 0x00
-        {
-            Ok(())
-        } else if response.len() >= 6 && response[5] != 0x00 {
-            Err(UhfError::InvalidResponse(format!(
-                "Write failed with error code: 0x{:02X}",
-                response[5]
-            )))
-        } else {
-            Err(UhfError::InvalidResponse("Failed to write tag data".into()))
+        match Self::tag_access_payload(&response, Self::WRITE_TAG_DATA)? {
+            Ok([0x00]) => Ok(Ok(())),
+            Ok(_) => Err(UhfError::InvalidResponse("Failed to write tag data".into())),
+            Err(code) => Ok(Err(code)),
+        }
+    }
+
+    /// Snapshot all four memory banks of the tag in the field
+    ///
+    /// Banks are read in chunks through `read_tag_data`. When a chunk fails, its
+    /// words are read one by one to record which are locked and where the bank
+    /// ends. Reading stops at 4 words for Reserved, 32 for EPC and TID, and 256 for
+    /// User memory. Other read failures, such as the tag leaving the field, abort
+    /// the dump.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn dump_tag(&mut self, access_password: &[u8; 4]) -> Result<TagMemoryImage, UhfError> {
+        let mut image = TagMemoryImage::default();
+        for (bank, limit) in [
+            (MemoryBank::Reserved, 4),
+            (MemoryBank::Epc, 32),
+            (MemoryBank::Tid, 32),
+            (MemoryBank::User, 256),
+        ] {
+            *image.bank_mut(bank) = self.dump_bank(access_password, bank, limit)?;
+        }
+        Ok(image)
+    }
+
+    fn dump_bank(
+        &mut self,
+        access_password: &[u8; 4],
+        bank: MemoryBank,
+        limit: usize,
+    ) -> Result<Vec<MemoryWord>, UhfError> {
+        let mut words = Vec::new();
+
+        while words.len() < limit {
+            let start = words.len();
+            let count = (limit - start).min(Self::DUMP_CHUNK_WORDS);
+            let chunk = self.try_read_tag_data(access_password, bank, start as u8, count as u8)?;
+            if let Ok(data) = chunk {
+                Self::push_words(&mut words, &data, count)?;
+                continue;
+            }
+
+            // Find out which word of the chunk failed
+            for word_ptr in start..start + count {
+                match self.try_read_tag_data(access_password, bank, word_ptr as u8, 1)? {
+                    Ok(data) => Self::push_words(&mut words, &data, 1)?,
+                    Err(code) if Self::is_memory_locked(code, Self::READ_ERROR) => {
+                        words.push(MemoryWord::Locked)
+                    }
+                    Err(code) if code == Self::READ_ERROR | Self::TAG_MEMORY_OVERRUN => {
+                        words.push(MemoryWord::OutOfRange);
+                        return Ok(words);
+                    }
+                    Err(code) => {
+                        return Err(UhfError::InvalidResponse(format!(
+                            "Reading {:?} word {} failed with error code: 0x{:02X}",
+                            bank, word_ptr, code
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(words)
+    }
+
+    fn push_words(words: &mut Vec<MemoryWord>, data: &[u8], count: usize) -> Result<(), UhfError> {
+        if data.len() != count * 2 {
+            return Err(UhfError::InvalidResponse(format!(
+                "Expected {} words but the tag returned {} bytes",
+                count,
+                data.len()
+            )));
+        }
+        words.extend(
+            data.chunks_exact(2)
+                .map(|word| MemoryWord::Data(u16::from_be_bytes([word[0], word[1]]))),
+        );
+        Ok(())
+    }
+
+    /// Write the words of a memory image back to the tag in the field
+    ///
+    /// Readable words of the EPC, User and Reserved banks are written in runs; TID
+    /// is factory programmed and the EPC bank's StoredCRC is computed by the tag,
+    /// so both are skipped. Words the tag refuses to write because they are locked
+    /// are listed in the report, other write failures abort the restore.
+    ///
+    /// `access_password` must open the tag as it is now. The passwords are written
+    /// last, the kill password before the access password, so a new access password
+    /// from the image only takes effect once everything else is written.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn restore_tag(
+        &mut self,
+        access_password: &[u8; 4],
+        image: &TagMemoryImage,
+    ) -> Result<RestoreReport, UhfError> {
+        let mut report = RestoreReport::default();
+        let reserved_len = image.reserved.len();
+
+        let segments = [
+            (MemoryBank::Epc, 1..image.epc.len()),
+            (MemoryBank::User, 0..image.user.len()),
+            // Kill password, any words past the passwords, then the access password
+            (MemoryBank::Reserved, 0..reserved_len.min(2)),
+            (MemoryBank::Reserved, 4..reserved_len.max(4)),
+            (MemoryBank::Reserved, reserved_len.min(2)..reserved_len.min(4)),
+        ];
+        for (bank, words) in segments {
+            self.restore_words(access_password, bank, image.bank(bank), words, &mut report)?;
+        }
+
+        Ok(report)
+    }
+
+    /// Write the readable words of `image_words[range]` in runs
+    fn restore_words(
+        &mut self,
+        access_password: &[u8; 4],
+        bank: MemoryBank,
+        image_words: &[MemoryWord],
+        range: Range<usize>,
+        report: &mut RestoreReport,
+    ) -> Result<(), UhfError> {
+        let end = range.end.min(256);
+        let mut word_ptr = range.start;
+
+        while word_ptr < end {
+            let run: Vec<u16> = image_words[word_ptr..end]
+                .iter()
+                .take(Self::MAX_WRITE_WORDS)
+                .map_while(|word| word.data())
+                .collect();
+            if run.is_empty() {
+                word_ptr += 1;
+                continue;
+            }
+
+            let data: Vec<u8> = run.iter().flat_map(|word| word.to_be_bytes()).collect();
+            match self.try_write_tag_data(access_password, bank, word_ptr as u8, &data)? {
+                Ok(()) => report.written_words += run.len(),
+                Err(code) if Self::is_memory_locked(code, Self::WRITE_ERROR) => {
+                    // Find out which words of the run are locked
+                    for (offset, word) in data.chunks_exact(2).enumerate() {
+                        let ptr = (word_ptr + offset) as u8;
+                        match self.try_write_tag_data(access_password, bank, ptr, word)? {
+                            Ok(()) => report.written_words += 1,
+                            Err(code) if Self::is_memory_locked(code, Self::WRITE_ERROR) => {
+                                report.locked_words.push((bank, ptr))
+                            }
+                            Err(code) => return Err(Self::restore_error(bank, ptr, code)),
+                        }
+                    }
+                }
+                Err(code) => return Err(Self::restore_error(bank, word_ptr as u8, code)),
+            }
+            word_ptr += run.len();
+        }
+
+        Ok(())
+    }
+
+    fn restore_error(bank: MemoryBank, word_ptr: u8, code: u8) -> UhfError {
+        UhfError::InvalidResponse(format!(
+            "Writing {:?} word {} failed with error code: 0x{:02X}",
+            bank, word_ptr, code
+        ))
+    }
+
+    /// Whether a read or write error code means the memory is locked
+    ///
+    /// A wrong access password is reported as an access failure instead.
+    fn is_memory_locked(code: u8, operation_error: u8) -> bool {
+        code == operation_error | Self::TAG_MEMORY_LOCKED || code == Self::ACCESS_FAIL
+    }
+
+    /// Change the access password of the tag in the field
+    ///
+    /// Writes Reserved bank words 2-3 using the `current` access password, then reads
+    /// them back with the new one. A zero password removes access protection.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn set_access_password(
+        &mut self,
+        current: &Password,
+        new: &Password,
+    ) -> Result<(), UhfError> {
+        self.set_password(current, Self::ACCESS_PASSWORD_WORD, new)?;
+        if !self.verify_access_password(new)? {
+            return Err(UhfError::InvalidResponse(
+                "Access password readback does not match".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Change the kill password of the tag in the field
+    ///
+    /// Writes Reserved bank words 0-1 and reads them back, both using the access
+    /// password. A zero kill password makes the tag unkillable.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn set_kill_password(
+        &mut self,
+        access_password: &Password,
+        kill_password: &Password,
+    ) -> Result<(), UhfError> {
+        self.set_password(access_password, Self::KILL_PASSWORD_WORD, kill_password)?;
+        let readback = self.read_tag_data(
+            access_password.as_bytes(),
+            MemoryBank::Reserved,
+            Self::KILL_PASSWORD_WORD,
+            2,
+        )?;
+        if readback != kill_password.as_bytes() {
+            return Err(UhfError::InvalidResponse(
+                "Kill password readback does not match".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check whether `password` is the access password of the tag in the field
+    ///
+    /// Reads Reserved bank words 2-3 with the password. The password is wrong if the
+    /// tag denies access, or if the words are readable but hold a different value.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn verify_access_password(&mut self, password: &Password) -> Result<bool, UhfError> {
+        let result = self.try_read_tag_data(
+            password.as_bytes(),
+            MemoryBank::Reserved,
+            Self::ACCESS_PASSWORD_WORD,
+            2,
+        )?;
+        match result {
+            Ok(data) => Ok(data == password.as_bytes()),
+            Err(code) if Self::is_memory_locked(code, Self::READ_ERROR) => Ok(false),
+            Err(code) => Err(UhfError::InvalidResponse(format!(
+                "Read failed with error code: 0x{:02X}",
+                code
+            ))),
         }
     }
 
+    fn set_password(
+        &mut self,
+        access_password: &Password,
+        word_ptr: u8,
+        password: &Password,
+    ) -> Result<(), UhfError> {
+        self.write_tag_data(
+            access_password.as_bytes(),
+            MemoryBank::Reserved,
+            word_ptr,
+            password.as_bytes(),
+        )
+    }
+
     /// Lock a tag's memory or password area
     ///
     /// # Arguments
@@ -852,6 +1994,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// # Warning
     /// Permanent lock operations are irreversible!
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn lock_tag(
         &mut self,
         access_password: &[u8; 4],
@@ -863,7 +2006,13 @@ impl<T: RfidTransport> UhfRfid<T> {
         params.extend_from_slice(access_password);
         params.extend_from_slice(&lock_bytes);
 
-        let response = self.exec(&Self::create_command(Self::LOCK_TAG, &params))?;
+        let command = Self::create_command(Self::LOCK_TAG, &params);
+        let permanent =
+            matches!(lock_payload.action, LockAction::PermLock | LockAction::PermUnlock);
+        if permanent {
+            self.guard_irreversible(&command)?;
+        }
+        let response = self.exec(&command)?;
 
         if response.len() >= 7
             && response[0] == Self::HEADER
@@ -882,6 +2031,179 @@ impl<T: RfidTransport> UhfRfid<T> {
         }
     }
 
+    /// Find out how each lock area of the tag in the field is locked, without writing
+    ///
+    /// `access_password` must be the tag's access password, zero if it has none.
+    /// Password areas are probed by reading them: an area readable without a
+    /// password is unlocked, one that needs the access password is locked, and one
+    /// that refuses even that is permalocked. Bank locks only restrict writes, so
+    /// reads cannot tell them apart and the EPC, TID and User banks are reported as
+    /// unknown; see [`probe_lock_state_with_writes`](Self::probe_lock_state_with_writes).
+    ///
+    /// A tag whose access password is zero is always in the secured state, where a
+    /// locked area is as accessible as an unlocked one. Such accessible areas are
+    /// reported as unknown.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn probe_lock_state(&mut self, access_password: &[u8; 4]) -> Result<LockStatus, UhfError> {
+        self.probe_locks(access_password, false)
+    }
+
+    /// Find out how each lock area is locked, probing the EPC and User banks by writing
+    ///
+    /// Like [`probe_lock_state`](Self::probe_lock_state), but the EPC and User
+    /// banks are probed by writing a word back with its current value: the first
+    /// EPC word and User word 0. A bank that is missing or unreadable is reported as
+    /// unknown. TID is factory programmed and never written, so it stays unknown.
+    ///
+    /// # Warning
+    /// The write-back probes use up write cycles, and a word can be corrupted if the
+    /// tag loses power while it is rewritten.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn probe_lock_state_with_writes(
+        &mut self,
+        access_password: &[u8; 4],
+    ) -> Result<LockStatus, UhfError> {
+        self.probe_locks(access_password, true)
+    }
+
+    fn probe_locks(
+        &mut self,
+        access_password: &[u8; 4],
+        allow_write_probe: bool,
+    ) -> Result<LockStatus, UhfError> {
+        let mut status = LockStatus {
+            kill_password: self.probe_password_lock(access_password, Self::KILL_PASSWORD_WORD)?,
+            access_password: self.probe_password_lock(access_password, Self::ACCESS_PASSWORD_WORD)?,
+            epc: LockState::Unknown,
+            tid: LockState::Unknown,
+            user: LockState::Unknown,
+        };
+        if allow_write_probe {
+            status.epc = self.probe_bank_lock(access_password, MemoryBank::Epc, 2)?;
+            status.user = self.probe_bank_lock(access_password, MemoryBank::User, 0)?;
+        }
+        Ok(status)
+    }
+
+    fn probe_password_lock(
+        &mut self,
+        access_password: &[u8; 4],
+        word_ptr: u8,
+    ) -> Result<LockState, UhfError> {
+        self.probe_lock(access_password, Self::READ_ERROR, |rfid, password| {
+            let result = rfid.try_read_tag_data(password, MemoryBank::Reserved, word_ptr, 2)?;
+            Ok(result.map(|_| ()))
+        })
+    }
+
+    fn probe_bank_lock(
+        &mut self,
+        access_password: &[u8; 4],
+        bank: MemoryBank,
+        word_ptr: u8,
+    ) -> Result<LockState, UhfError> {
+        let word = match self.try_read_tag_data(access_password, bank, word_ptr, 1)? {
+            Ok(word) => word,
+            Err(code)
+                if code == Self::READ_ERROR | Self::TAG_MEMORY_OVERRUN
+                    || Self::is_memory_locked(code, Self::READ_ERROR) =>
+            {
+                return Ok(LockState::Unknown);
+            }
+            Err(code) => return Err(Self::probe_error(code)),
+        };
+
+        self.probe_lock(access_password, Self::WRITE_ERROR, |rfid, password| {
+            rfid.try_write_tag_data(password, bank, word_ptr, &word)
+        })
+    }
+
+    /// Classify an access attempted without and then with the access password
+    fn probe_lock<F>(
+        &mut self,
+        access_password: &[u8; 4],
+        operation_error: u8,
+        mut attempt: F,
+    ) -> Result<LockState, UhfError>
+    where
+        F: FnMut(&mut Self, &[u8; 4]) -> Result<Result<(), u8>, UhfError>,
+    {
+        // A tag without an access password is always in the secured state, where
+        // locked areas are as accessible as unlocked ones
+        let secured = access_password == &[0; 4];
+        match attempt(self, &[0; 4])? {
+            Ok(()) if secured => return Ok(LockState::Unknown),
+            Ok(()) => return Ok(LockState::Unlocked),
+            Err(code) if Self::is_memory_locked(code, operation_error) => {}
+            Err(code) => return Err(Self::probe_error(code)),
+        }
+
+        if secured {
+            return Ok(LockState::PermaLocked);
+        }
+
+        match attempt(self, access_password)? {
+            Ok(()) => Ok(LockState::Locked),
+            Err(Self::ACCESS_FAIL) => Err(UhfError::InvalidParameter(
+                "Access password rejected by the tag".into(),
+            )),
+            Err(code) if code == operation_error | Self::TAG_MEMORY_LOCKED => {
+                Ok(LockState::PermaLocked)
+            }
+            Err(code) => Err(Self::probe_error(code)),
+        }
+    }
+
+    fn probe_error(code: u8) -> UhfError {
+        UhfError::InvalidResponse(format!("Lock probe failed with error code: 0x{:02X}", code))
+    }
+
+    /// Apply the guard mode to an irreversible command, failing unless it may be sent
+    fn guard_irreversible(&mut self, command: &[u8]) -> Result<(), UhfError> {
+        if self.guard_mode == GuardMode::Off {
+            return Ok(());
+        }
+        let Some(confirmation) = self.confirmation.take() else {
+            return Err(UhfError::InvalidParameter(
+                "Irreversible command needs a confirmation in guarded mode".into(),
+            ));
+        };
+
+        let tags = self.inventory(Self::GUARD_INVENTORY_ROUNDS, &InventoryOptions::default())?;
+        let [tag] = tags.as_slice() else {
+            return Err(UhfError::InvalidParameter(format!(
+                "Irreversible command needs exactly one tag in the field, found {}",
+                tags.len()
+            )));
+        };
+
+        let matches = match &confirmation {
+            Confirmation::Epc(epc) => tag.epc.eq_ignore_ascii_case(epc),
+            Confirmation::Tid(tid) => {
+                if tid.is_empty() || !tid.len().is_multiple_of(2) || tid.len() > 2 * 255 {
+                    return Err(UhfError::InvalidParameter(
+                        "Confirmed TID must be 1 to 255 whole words".into(),
+                    ));
+                }
+                let words = (tid.len() / 2) as u8;
+                self.read_tag_data(&[0; 4], MemoryBank::Tid, 0, words)? == *tid
+            }
+        };
+        if !matches {
+            return Err(UhfError::InvalidParameter(format!(
+                "Tag {} in the field does not match the confirmation",
+                tag.epc
+            )));
+        }
+
+        if self.guard_mode == GuardMode::DryRun {
+            let frame = self.redacted(command).to_string();
+            info!("Dry run, not sending irreversible command: {}", frame);
+            return Err(UhfError::DryRun(frame));
+        }
+        Ok(())
+    }
+
     /// Kill (permanently disable) a tag
     ///
     /// # Arguments
@@ -889,6 +2211,9 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// # Warning
     /// This operation is irreversible! The tag will be permanently disabled.
+    /// Use [`set_guard_mode`](Self::set_guard_mode) to make sure only the intended
+    /// tag is in the field.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn kill_tag(&mut self, kill_password: &[u8; 4]) -> Result<(), UhfError> {
         // Kill password must be non-zero per EPC Gen2 spec
         if kill_password == &[0, 0, 0, 0] {
@@ -897,7 +2222,9 @@ impl<T: RfidTransport> UhfRfid<T> {
             ));
         }
 
-        let response = self.exec(&Self::create_command(Self::KILL_TAG, kill_password))?;
+        let command = Self::create_command(Self::KILL_TAG, kill_password);
+        self.guard_irreversible(&command)?;
+        let response = self.exec(&command)?;
 
         if response.len() >= 7
             && response[0] == Self::HEADER
@@ -928,6 +2255,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// # Arguments
     /// * `rounds` - Number of inventory rounds to perform
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn inventory_buffer(&mut self, rounds: u16) -> Result<(), UhfError> {
         if rounds == 0 {
             return Err(UhfError::InvalidParameter(
@@ -957,60 +2285,66 @@ impl<T: RfidTransport> UhfRfid<T> {
 
     /// Get tag data stored in reader buffer
     ///
-    /// Returns the tags stored in the buffer from a previous `inventory_buffer()` call.
-    pub fn get_buffer_data(&mut self) -> Result<Vec<TagInfo>, UhfError> {
-        let response = self.exec(&Self::create_command(Self::GET_BUFFER_DATA, &[]))?;
-
-        if response.len() < 7
-            || response[0] != Self::HEADER
-            || response[1] != Self::RESP_TYPE_NOTIFICATION
-            || response[2] != Self::GET_BUFFER_DATA
-        {
+    /// Returns the tags stored by a previous `inventory_buffer()` call. The module
+    /// sends the buffer as one or more frames of entries, each laid out as read
+    /// count (2), RSSI (1), PC (2), EPC, CRC (2), where the EPC length comes from
+    /// the PC word. The dump ends with a frame holding a single 0x00 byte, which is
+    /// also the answer for an empty buffer.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(tags = tracing::field::Empty))
+    )]
+    pub fn get_buffer_data(&mut self) -> Result<Vec<TagReport>, UhfError> {
+        let (frames, _) = self.exec_until(
+            &Self::create_command(Self::GET_BUFFER_DATA, &[]),
+            |frame| Self::is_buffer_end(frame.as_bytes()),
+        )?;
+        if frames.is_empty() {
             return Err(UhfError::InvalidResponse("Invalid buffer response".into()));
         }
 
-        let data_len = ((response[3] as usize) << 8) | (response[4] as usize);
-        if data_len == 1 && response[5] == 0x00 {
-            // Empty buffer
-            return Ok(Vec::new());
-        }
-
-        // Parse tags from buffer - format varies by reader
-        // This is a simplified implementation
-        let mut tags = Vec::new();
-        let mut offset = 5;
-
-        while offset + 4 < response.len() - 2 {
-            // Try to parse tag entries
-            // Format: RSSI (1) + PC (2) + EPC (variable)
-            if response[offset] == 0x00 || offset >= data_len + 5 {
-                break;
+        let mut reports = Vec::new();
+        for frame in frames.iter().map(Frame::as_bytes) {
+            if frame[1] != Self::RESP_TYPE_NOTIFICATION || frame[2] != Self::GET_BUFFER_DATA {
+                return Err(UhfError::InvalidResponse(format!(
+                    "Invalid buffer response: {:02X?}",
+                    frame
+                )));
             }
-
-            let rssi = response[offset];
-            let epc_len = if offset + 3 < response.len() {
-                // Assume 12-byte EPC by default
-                12usize.min(response.len() - offset - 3)
-            } else {
-                break;
-            };
-
-            if offset + 3 + epc_len <= response.len() {
-                let epc_bytes = &response[offset + 3..offset + 3 + epc_len];
-                tags.push(TagInfo {
-                    epc: bytes_to_hex(epc_bytes),
-                    rssi,
-                });
-                offset += 3 + epc_len;
-            } else {
+            if Self::is_buffer_end(frame) {
                 break;
             }
+            Self::parse_buffer_entries(&frame[5..frame.len() - 2], &mut reports)?;
         }
 
-        Ok(tags)
+        trace::record_tags(reports.len());
+        Ok(reports)
+    }
+
+    /// Run a buffered inventory and collect its results
+    ///
+    /// Runs `inventory_buffer(rounds)`, reads the buffer with `get_buffer_data()`
+    /// and clears it for the next run.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(tags = tracing::field::Empty))
+    )]
+    pub fn buffered_inventory(&mut self, rounds: u16) -> Result<Vec<TagReport>, UhfError> {
+        let start = self.clock.now();
+        self.inventory_buffer(rounds)?;
+        let reports = self.get_buffer_data()?;
+        self.clear_buffer()?;
+
+        for report in &reports {
+            self.record_reads(&report.epc, report.rssi, report.read_count as usize);
+        }
+        self.record_inventory(rounds, start, 0, 0);
+        trace::record_tags(reports.len());
+        Ok(reports)
     }
 
     /// Clear the reader's tag buffer
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn clear_buffer(&mut self) -> Result<(), UhfError> {
         let response = self.exec(&Self::create_command(Self::CLEAR_BUFFER, &[]))?;
 
@@ -1027,6 +2361,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     }
 
     /// Get current RF link profile
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn get_rf_link_profile(&mut self) -> Result<RfLinkProfile, UhfError> {
         let response = self.exec(&Self::create_command(Self::GET_RF_LINK_PROFILE, &[]))?;
 
@@ -1051,6 +2386,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     /// Set RF link profile
     ///
     /// This configures the modulation and data rate settings.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn set_rf_link_profile(&mut self, profile: RfLinkProfile) -> Result<(), UhfError> {
         let response = self.exec(&Self::create_command(
             Self::SET_RF_LINK_PROFILE,
@@ -1069,94 +2405,258 @@ impl<T: RfidTransport> UhfRfid<T> {
         }
     }
 
-    /// Get current reader sensitivity
-    ///
-    /// Returns the sensitivity value (higher = less sensitive, lower = more sensitive).
-    pub fn get_reader_sensitivity(&mut self) -> Result<u8, UhfError> {
-        let response = self.exec(&Self::create_command(Self::GET_READER_SENSITIVITY, &[]))?;
+    /// Get current receiver demodulator parameters
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn get_demodulator_params(&mut self) -> Result<DemodulatorParams, UhfError> {
+        let response = self.exec(&Self::create_command(Self::GET_DEMODULATOR_PARAMS, &[]))?;
 
-        if response.len() >= 8
+        if response.len() >= 11
             && response[0] == Self::HEADER
             && response[1] == Self::RESP_TYPE_NOTIFICATION
-            && response[2] == Self::GET_READER_SENSITIVITY
+            && response[2] == Self::GET_DEMODULATOR_PARAMS
             && response[3] == 0x00
-            && response[4] == 0x01
+            && response[4] == 0x04
         {
-            Ok(response[5])
+            let bytes = [response[5], response[6], response[7], response[8]];
+            DemodulatorParams::try_from(bytes).map_err(|_| {
+                UhfError::InvalidResponse(format!(
+                    "Unknown demodulator gain setting: mixer 0x{:02X}, IF 0x{:02X}",
+                    bytes[0], bytes[1]
+                ))
+            })
         } else {
-            Err(UhfError::InvalidResponse("Failed to get reader sensitivity".into()))
+            Err(UhfError::InvalidResponse("Failed to get demodulator parameters".into()))
         }
     }
 
-    /// Set reader sensitivity
+    /// Set receiver demodulator parameters
     ///
-    /// # Arguments
-    /// * `sensitivity` - Sensitivity value (valid range typically 0-31, check reader specs)
-    pub fn set_reader_sensitivity(&mut self, sensitivity: u8) -> Result<(), UhfError> {
+    /// A lower threshold demodulates weaker tag replies at the cost of stability.
+    /// 0x01B0 is the lowest value recommended by the module vendor.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn set_demodulator_params(&mut self, params: &DemodulatorParams) -> Result<(), UhfError> {
+        if params.threshold < DemodulatorParams::MIN_RECOMMENDED_THRESHOLD {
+            warn!(
+                "Demodulation threshold 0x{:04X} is below the recommended minimum 0x{:04X}",
+                params.threshold,
+                DemodulatorParams::MIN_RECOMMENDED_THRESHOLD
+            );
+        }
+
         let response = self.exec(&Self::create_command(
-            Self::SET_READER_SENSITIVITY,
-            &[sensitivity],
+            Self::SET_DEMODULATOR_PARAMS,
+            &params.to_bytes(),
         ))?;
 
         if response.len() >= 7
             && response[0] == Self::HEADER
             && response[1] == Self::RESP_TYPE_NOTIFICATION
-            && response[2] == Self::SET_READER_SENSITIVITY
+            && response[2] == Self::SET_DEMODULATOR_PARAMS
             && response[5] == 0x00
         {
             Ok(())
         } else {
-            Err(UhfError::InvalidResponse("Failed to set reader sensitivity".into()))
+            Err(UhfError::InvalidResponse("Failed to set demodulator parameters".into()))
         }
     }
 
+    /// Get current reader sensitivity
+    ///
+    /// The module has no separate sensitivity setting; commands 0xF0/0xF1 carry the
+    /// demodulator parameters. The sensitivity is the demodulation threshold divided
+    /// by 16, rounded down and capped at 255 (higher = less sensitive), so thresholds
+    /// that are not a multiple of 16 cannot be read back exactly.
+    #[deprecated(note = "use `get_demodulator_params`; the sensitivity is threshold / 16")]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn get_reader_sensitivity(&mut self) -> Result<u8, UhfError> {
+        let params = self.get_demodulator_params()?;
+        Ok((params.threshold >> 4).min(u8::MAX as u16) as u8)
+    }
+
+    /// Set reader sensitivity
+    ///
+    /// Reads the demodulator parameters, sets the threshold to `sensitivity * 16` and
+    /// writes them back with the gains unchanged, so it costs two commands.
+    ///
+    /// # Arguments
+    /// * `sensitivity` - Demodulation threshold divided by 16
+    #[deprecated(note = "use `set_demodulator_params`; the threshold is sensitivity * 16")]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn set_reader_sensitivity(&mut self, sensitivity: u8) -> Result<(), UhfError> {
+        let mut params = self.get_demodulator_params()?;
+        params.threshold = (sensitivity as u16) << 4;
+        self.set_demodulator_params(&params)
+    }
+
+    /// Measure the RF input blocking signal on every channel of the current region
+    ///
+    /// Useful to detect strong interferers that desensitize the receiver.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn measure_blocking_signal(&mut self) -> Result<SpectrumReport, UhfError> {
+        self.scan_spectrum(Self::SCAN_JAMMER)
+    }
+
+    /// Measure the received signal level on every channel of the current region
+    ///
+    /// High levels indicate other readers or noise sources operating nearby.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn scan_channel_rssi(&mut self) -> Result<SpectrumReport, UhfError> {
+        self.scan_spectrum(Self::SCAN_RSSI)
+    }
+
+    fn scan_spectrum(&mut self, command: u8) -> Result<SpectrumReport, UhfError> {
+        let region = self.get_region()?;
+        let response = self.exec(&Self::create_command(command, &[]))?;
+
+        // Response format: BB 01 CMD LL LL CH_L CH_H [level per channel...] checksum 7E
+        if response.len() < 9
+            || response[0] != Self::HEADER
+            || response[1] != Self::RESP_TYPE_NOTIFICATION
+            || response[2] != command
+        {
+            return Err(UhfError::InvalidResponse("Invalid channel scan response".into()));
+        }
+
+        let data_len = ((response[3] as usize) << 8) | (response[4] as usize);
+        let start_channel = response[5];
+        let end_channel = response[6];
+
+        if end_channel < start_channel {
+            return Err(UhfError::InvalidResponse(format!(
+                "Invalid channel range in scan response: {}-{}",
+                start_channel, end_channel
+            )));
+        }
+
+        let channel_count = (end_channel - start_channel) as usize + 1;
+        if data_len != 2 + channel_count || response.len() < 7 + channel_count + 2 {
+            return Err(UhfError::InvalidResponse("Channel scan response too short".into()));
+        }
+
+        let levels_dbm = response[7..7 + channel_count].iter().map(|&b| b as i8).collect();
+
+        Ok(SpectrumReport {
+            region,
+            start_channel,
+            levels_dbm,
+        })
+    }
+
     /// Block Permalock - permanently lock memory blocks
     ///
+    /// Uses the lock mode of BlockPermalock. Like
+    /// [`read_block_permalock_status`](Self::read_block_permalock_status),
+    /// `block_ptr` counts in units of 16 blocks and each mask word covers 16 blocks,
+    /// first block in the most significant bit.
+    ///
     /// # Arguments
     /// * `access_password` - 4-byte access password
     /// * `mem_bank` - Memory bank (User bank only, typically)
-    /// * `block_ptr` - Starting block number
-    /// * `block_range` - Number of blocks to lock
-    /// * `mask` - 16-bit mask specifying which blocks to permalock
+    /// * `block_ptr` - Starting block, in units of 16 blocks
+    /// * `mask` - 1 to 255 mask words; set bits select the blocks to permalock
     ///
     /// # Warning
     /// This operation is irreversible!
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn block_permalock(
         &mut self,
         access_password: &[u8; 4],
         mem_bank: MemoryBank,
-        block_ptr: u8,
-        block_range: u8,
-        mask: u16,
+        block_ptr: u16,
+        mask: &[u16],
     ) -> Result<(), UhfError> {
-        let mask_msb = (mask >> 8) as u8;
-        let mask_lsb = (mask & 0xFF) as u8;
+        let block_range = match u8::try_from(mask.len()) {
+            Ok(range) if range > 0 => range,
+            _ => {
+                return Err(UhfError::InvalidParameter(
+                    "Mask must hold 1 to 255 words".into(),
+                ));
+            }
+        };
+
+        let mut params = Vec::with_capacity(9 + 2 * mask.len());
+        params.extend_from_slice(access_password);
+        params.push(Self::PERMALOCK_LOCK);
+        params.push(mem_bank as u8);
+        params.extend_from_slice(&block_ptr.to_be_bytes());
+        params.push(block_range);
+        params.extend(mask.iter().flat_map(|word| word.to_be_bytes()));
+
+        let command = Self::create_command(Self::BLOCK_PERMALOCK, &params);
+        self.guard_irreversible(&command)?;
+        let response = self.exec(&command)?;
+
+        // Response format: BB 01 D4 PL PL UL [PC+EPC] status checksum 7E
+        match Self::tag_access_payload(&response, Self::BLOCK_PERMALOCK_LOCKED)? {
+            Ok([0x00]) => Ok(()),
+            Ok(_) => Err(UhfError::InvalidResponse(format!(
+                "Invalid block permalock response: {:02X?}",
+                response
+            ))),
+            Err(code) => Err(UhfError::InvalidResponse(format!(
+                "Block permalock failed with error code: 0x{:02X}",
+                code
+            ))),
+        }
+    }
+
+    /// Read the permalock status of memory blocks
+    ///
+    /// Uses the read mode of BlockPermalock. `block_ptr` and `block_range` count in
+    /// units of 16 blocks, so the result holds `16 * block_range` flags starting at
+    /// block `16 * block_ptr`, where `true` marks a permalocked block.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn read_block_permalock_status(
+        &mut self,
+        access_password: &[u8; 4],
+        mem_bank: MemoryBank,
+        block_ptr: u16,
+        block_range: u8,
+    ) -> Result<Vec<bool>, UhfError> {
+        if block_range == 0 {
+            return Err(UhfError::InvalidParameter(
+                "Block range must be at least 1".into(),
+            ));
+        }
 
         let mut params = Vec::with_capacity(9);
         params.extend_from_slice(access_password);
+        params.push(Self::PERMALOCK_READ);
         params.push(mem_bank as u8);
-        params.push(block_ptr);
+        params.extend_from_slice(&block_ptr.to_be_bytes());
         params.push(block_range);
-        params.push(mask_msb);
-        params.push(mask_lsb);
 
         let response = self.exec(&Self::create_command(Self::BLOCK_PERMALOCK, &params))?;
 
-        if response.len() >= 7
-            && response[0] == Self::HEADER
-            && response[1] == Self::RESP_TYPE_NOTIFICATION
-            && response[2] == Self::BLOCK_PERMALOCK
-            && response[5] == 0x00
-        {
-            Ok(())
-        } else if response.len() >= 6 && response[5] != 0x00 {
-            Err(UhfError::InvalidResponse(format!(
-                "Block permalock failed with error code: 0x{:02X}",
+        // Response format: BB 01 D3 PL PL UL [PC+EPC] BlockRange [mask words] checksum 7E
+        // or error: BB 01 FF 00 01 EE checksum 7E
+        if response.len() < 8 || response[0] != Self::HEADER {
+            return Err(UhfError::InvalidResponse("Invalid block permalock response".into()));
+        }
+        if response[2] == frame::ERROR_COMMAND {
+            return Err(UhfError::InvalidResponse(format!(
+                "Block permalock read failed with error code: 0x{:02X}",
                 response[5]
-            )))
-        } else {
-            Err(UhfError::InvalidResponse("Failed to block permalock".into()))
+            )));
         }
+
+        let params = &response[5..response.len() - 2];
+        let mask_start = 1 + params[0] as usize + 1;
+        if response[1] != Self::RESP_TYPE_NOTIFICATION
+            || response[2] != Self::BLOCK_PERMALOCK
+            || params.len() != mask_start + 2 * block_range as usize
+            || params[mask_start - 1] != block_range
+        {
+            return Err(UhfError::InvalidResponse(format!(
+                "Invalid block permalock response: {:02X?}",
+                response
+            )));
+        }
+
+        Ok(params[mask_start..]
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |bit| byte & (1 << bit) != 0))
+            .collect())
     }
 
     // NXP-specific commands
@@ -1165,6 +2665,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// # Arguments
     /// * `access_password` - 4-byte access password
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn nxp_read_protect(&mut self, access_password: &[u8; 4]) -> Result<(), UhfError> {
         let response = self.exec(&Self::create_command(Self::NXP_READ_PROTECT, access_password))?;
 
@@ -1189,6 +2690,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     ///
     /// # Arguments
     /// * `access_password` - 4-byte access password
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn nxp_reset_read_protect(&mut self, access_password: &[u8; 4]) -> Result<(), UhfError> {
         let response = self.exec(&Self::create_command(
             Self::NXP_RESET_READ_PROTECT,
@@ -1217,6 +2719,7 @@ impl<T: RfidTransport> UhfRfid<T> {
     /// # Arguments
     /// * `access_password` - 4-byte access password
     /// * `enabled` - true to enable EAS, false to disable
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn nxp_change_eas(&mut self, access_password: &[u8; 4], enabled: bool) -> Result<(), UhfError> {
         let mut params = Vec::with_capacity(5);
         params.extend_from_slice(access_password);
@@ -1243,53 +2746,71 @@ impl<T: RfidTransport> UhfRfid<T> {
 
     /// NXP EAS Alarm - check for EAS alarm on NXP tags
     ///
-    /// Returns true if an EAS-enabled tag is detected.
-    pub fn nxp_eas_alarm(&mut self) -> Result<bool, UhfError> {
+    /// Returns the 64-bit EAS alarm code sent by a tag with the PSF alarm bit set,
+    /// or `None` if no tag answered.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn nxp_eas_alarm(&mut self) -> Result<Option<u64>, UhfError> {
         let response = self.exec(&Self::create_command(Self::NXP_EAS_ALARM, &[]))?;
 
-        if response.len() >= 7 && response[0] == Self::HEADER {
-            if response[1] == Self::RESP_TYPE_TAG {
-                // Tag with EAS detected
-                Ok(true)
-            } else if response[1] == Self::RESP_TYPE_NOTIFICATION {
-                // No EAS tag detected or error
-                Ok(response[5] == 0x00)
-            } else {
-                Err(UhfError::InvalidResponse("Unexpected response type".into()))
+        // Response format: BB 01 E4 00 08 [alarm code] checksum 7E
+        // or error: BB 01 FF 00 01 1D checksum 7E when no tag answered
+        if response.len() == 15
+            && response[0] == Self::HEADER
+            && response[1] == Self::RESP_TYPE_NOTIFICATION
+            && response[2] == Self::NXP_EAS_ALARM
+        {
+            let mut code = [0u8; 8];
+            code.copy_from_slice(&response[5..13]);
+            Ok(Some(u64::from_be_bytes(code)))
+        } else if response.len() >= 7 && response[2] == frame::ERROR_COMMAND {
+            if response[5] == Self::EAS_ALARM_FAIL {
+                return Ok(None);
             }
+            Err(UhfError::InvalidResponse(format!(
+                "NXP EAS Alarm failed with error code: 0x{:02X}",
+                response[5]
+            )))
         } else {
             Err(UhfError::InvalidResponse("Failed to check EAS alarm".into()))
         }
     }
 
-    /// NXP Change Config - modify NXP UCODE tag configuration
+    /// NXP Change Config - toggle bits of the NXP UCODE configuration word
+    ///
+    /// Every bit set in `toggle_mask` is inverted, a zero mask only reads the word.
+    /// Returns the configuration word reported by the tag afterwards.
     ///
     /// # Arguments
     /// * `access_password` - 4-byte access password
-    /// * `config_word` - 16-bit configuration word
+    /// * `toggle_mask` - 16-bit mask of configuration bits to invert
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn nxp_change_config(
         &mut self,
         access_password: &[u8; 4],
-        config_word: u16,
-    ) -> Result<(), UhfError> {
-        let config_msb = (config_word >> 8) as u8;
-        let config_lsb = (config_word & 0xFF) as u8;
-
+        toggle_mask: u16,
+    ) -> Result<NxpConfigWord, UhfError> {
         let mut params = Vec::with_capacity(6);
         params.extend_from_slice(access_password);
-        params.push(config_msb);
-        params.push(config_lsb);
+        params.extend_from_slice(&toggle_mask.to_be_bytes());
 
         let response = self.exec(&Self::create_command(Self::NXP_CHANGE_CONFIG, &params))?;
 
-        if response.len() >= 7
+        // Response format: BB 01 E0 PL PL UL [PC+EPC] config(2) checksum 7E
+        if response.len() >= 10
             && response[0] == Self::HEADER
             && response[1] == Self::RESP_TYPE_NOTIFICATION
             && response[2] == Self::NXP_CHANGE_CONFIG
-            && response[5] == 0x00
         {
-            Ok(())
-        } else if response.len() >= 6 && response[5] != 0x00 {
+            let params = &response[5..response.len() - 2];
+            if params.len() != 1 + params[0] as usize + 2 {
+                return Err(UhfError::InvalidResponse(format!(
+                    "Invalid NXP Change Config response: {:02X?}",
+                    response
+                )));
+            }
+            let config = u16::from_be_bytes([params[params.len() - 2], params[params.len() - 1]]);
+            Ok(NxpConfigWord::from_raw(config))
+        } else if response.len() >= 6 && response[2] == frame::ERROR_COMMAND {
             Err(UhfError::InvalidResponse(format!(
                 "NXP Change Config failed with error code: 0x{:02X}",
                 response[5]
@@ -1299,42 +2820,255 @@ impl<T: RfidTransport> UhfRfid<T> {
         }
     }
 
+    /// Read the NXP UCODE configuration word
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn nxp_read_config(
+        &mut self,
+        access_password: &[u8; 4],
+    ) -> Result<NxpConfigWord, UhfError> {
+        self.nxp_change_config(access_password, 0)
+    }
+
+    /// Set or clear one flag of the NXP UCODE configuration word
+    ///
+    /// The word is read first so that only the flag's bit is toggled, and only if it
+    /// differs. Returns the resulting configuration word.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn nxp_set_config_flag(
+        &mut self,
+        access_password: &[u8; 4],
+        flag: NxpConfigFlag,
+        enabled: bool,
+    ) -> Result<NxpConfigWord, UhfError> {
+        let current = self.nxp_read_config(access_password)?;
+        let target = current.with(flag, enabled);
+        if current == target {
+            return Ok(current);
+        }
+
+        let config = self.nxp_change_config(access_password, current.toggle_mask(target))?;
+        if config.is_set(flag) != enabled {
+            return Err(UhfError::InvalidResponse(format!(
+                "NXP config flag {:?} did not change",
+                flag
+            )));
+        }
+        Ok(config)
+    }
+
+    /// Turn NXP tag focus on or off
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn nxp_set_tag_focus(
+        &mut self,
+        access_password: &[u8; 4],
+        enabled: bool,
+    ) -> Result<NxpConfigWord, UhfError> {
+        self.nxp_set_config_flag(access_password, NxpConfigFlag::TagFocus, enabled)
+    }
+
+    /// Turn backscattering of the NXP brand identifier on or off
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn nxp_set_brand_id(
+        &mut self,
+        access_password: &[u8; 4],
+        enabled: bool,
+    ) -> Result<NxpConfigWord, UhfError> {
+        self.nxp_set_config_flag(access_password, NxpConfigFlag::BrandId, enabled)
+    }
+
     /// Impinj Monza QT - configure QT settings on Impinj Monza tags
     ///
+    /// Runs [`impinj_qt_read`](Self::impinj_qt_read) or
+    /// [`impinj_qt_write`](Self::impinj_qt_write). A read returns the most
+    /// significant byte of the QT control word, which holds QT_SR and QT_MEM; a
+    /// write returns 0.
+    ///
     /// # Arguments
     /// * `access_password` - 4-byte access password
     /// * `qt_control` - QT control settings
     /// * `read` - true to read current QT settings, false to write
+    #[deprecated(note = "use `impinj_qt_read` or `impinj_qt_write`")]
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn impinj_monza_qt(
         &mut self,
         access_password: &[u8; 4],
         qt_control: &QtControl,
         read: bool,
     ) -> Result<u8, UhfError> {
-        let rw_flag = if read { 0x00 } else { 0x01 };
+        if read {
+            let [msb, _] = self.impinj_qt_read(access_password)?.to_word().to_be_bytes();
+            Ok(msb)
+        } else {
+            self.impinj_qt_write(access_password, qt_control)?;
+            Ok(0)
+        }
+    }
 
-        let mut params = Vec::with_capacity(6);
+    /// Read the QT control settings of an Impinj Monza tag
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn impinj_qt_read(&mut self, access_password: &[u8; 4]) -> Result<QtControl, UhfError> {
+        let response = self.exec_qt(access_password, Self::QT_READ, &QtControl::default())?;
+
+        // Response format: BB 01 E5 PL PL UL [PC+EPC] QT control(2) checksum 7E
+        let params = &response[5..response.len() - 2];
+        if response[2] != Self::IMPINJ_MONZA_QT || params.len() != 1 + params[0] as usize + 2 {
+            return Err(UhfError::InvalidResponse(format!(
+                "Invalid Impinj QT read response: {:02X?}",
+                response
+            )));
+        }
+        let word = u16::from_be_bytes([params[params.len() - 2], params[params.len() - 1]]);
+        Ok(QtControl::from_word(word))
+    }
+
+    /// Write the QT control settings of an Impinj Monza tag
+    ///
+    /// With `persistence` set the settings survive power loss, otherwise they only
+    /// last until the tag loses power.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn impinj_qt_write(
+        &mut self,
+        access_password: &[u8; 4],
+        qt_control: &QtControl,
+    ) -> Result<(), UhfError> {
+        let response = self.exec_qt(access_password, Self::QT_WRITE, qt_control)?;
+
+        // Response format: BB 01 E6 PL PL UL [PC+EPC] status checksum 7E
+        let params = &response[5..response.len() - 2];
+        if response[2] != Self::IMPINJ_MONZA_QT + 1 || params.last() != Some(&0x00) {
+            return Err(UhfError::InvalidResponse(format!(
+                "Invalid Impinj QT write response: {:02X?}",
+                response
+            )));
+        }
+        Ok(())
+    }
+
+    fn exec_qt(
+        &mut self,
+        access_password: &[u8; 4],
+        read_write: u8,
+        qt_control: &QtControl,
+    ) -> Result<Vec<u8>, UhfError> {
+        let mut params = Vec::with_capacity(8);
         params.extend_from_slice(access_password);
-        params.push(rw_flag);
-        params.push(qt_control.to_byte());
+        params.push(read_write);
+        params.push(qt_control.persistence as u8);
+        params.extend_from_slice(&qt_control.to_word().to_be_bytes());
 
         let response = self.exec(&Self::create_command(Self::IMPINJ_MONZA_QT, &params))?;
-
-        if response.len() >= 8
-            && response[0] == Self::HEADER
-            && response[1] == Self::RESP_TYPE_NOTIFICATION
-            && response[2] == Self::IMPINJ_MONZA_QT
-            && response[5] == 0x00
+        if response.len() < 8
+            || response[0] != Self::HEADER
+            || response[1] != Self::RESP_TYPE_NOTIFICATION
         {
-            Ok(response[6])
-        } else if response.len() >= 6 && response[5] != 0x00 {
-            Err(UhfError::InvalidResponse(format!(
-                "Impinj Monza QT failed with error code: 0x{:02X}",
+            return Err(UhfError::InvalidResponse("Invalid Impinj QT response".into()));
+        }
+        if response[2] == frame::ERROR_COMMAND {
+            return Err(UhfError::InvalidResponse(format!(
+                "Impinj QT failed with error code: 0x{:02X}",
                 response[5]
-            )))
-        } else {
-            Err(UhfError::InvalidResponse("Failed Impinj Monza QT operation".into()))
+            )));
+        }
+        Ok(response)
+    }
+
+    /// Switch an Impinj QT tag to its public or private profile
+    ///
+    /// The public profile presents the public memory map and only lets readers
+    /// access the tag at short range; the private profile presents the private
+    /// memory map at full range.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn impinj_set_profile(
+        &mut self,
+        access_password: &[u8; 4],
+        profile: QtProfile,
+        persistent: bool,
+    ) -> Result<(), UhfError> {
+        self.impinj_qt_write(access_password, &profile.control(persistent))
+    }
+
+    /// Read from the private memory map of an Impinj QT tag
+    ///
+    /// A tag in its public profile is switched to the private memory map for the
+    /// read, without persistence, and switched back afterwards even if the read
+    /// failed.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn impinj_read_private(
+        &mut self,
+        access_password: &[u8; 4],
+        mem_bank: MemoryBank,
+        word_ptr: u8,
+        word_count: u8,
+    ) -> Result<Vec<u8>, UhfError> {
+        let current = self.impinj_qt_read(access_password)?;
+        if current.private_memory {
+            return self.read_tag_data(access_password, mem_bank, word_ptr, word_count);
+        }
+
+        let private = QtControl { private_memory: true, ..current };
+        self.impinj_qt_write(access_password, &private)?;
+        let data = self.read_tag_data(access_password, mem_bank, word_ptr, word_count);
+        self.impinj_qt_write(access_password, &current)?;
+        data
+    }
+
+    // ========================================================================
+    // Raw Command Access
+    // ========================================================================
+
+    /// Send any command and return the frame answering it
+    ///
+    /// For commands the driver does not wrap. The response is matched like for the
+    /// wrapped commands: the first frame with the command's code, or the code it is
+    /// known to be acknowledged with, or an error frame, which is returned as is
+    /// (see [`Frame::error_code`]). Other frames are queued for
+    /// [`take_notifications`](Self::take_notifications).
+    ///
+    /// In guarded mode, raw kill, lock and BlockPermalock commands are treated as
+    /// irreversible, see [`set_guard_mode`](Self::set_guard_mode); a dry run fails
+    /// with [`UhfError::DryRun`] in both variants.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn send_raw(&mut self, command: u8, params: &[u8]) -> Result<Frame, UhfError> {
+        let mut frames = self.send_raw_streaming(command, params, |_| true)?;
+        Ok(frames.swap_remove(0))
+    }
+
+    /// Send any command and return every frame answering it up to a terminator
+    ///
+    /// For commands answered by several frames, such as inventories. Frames are
+    /// collected until `last` returns true for one of them, or until the command's
+    /// response deadline passes without another frame, in which case the frames
+    /// received so far are returned. Frames that fail the checksum or end marker
+    /// check are skipped and counted in [`rejected_frames`](Self::rejected_frames).
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn send_raw_streaming<F>(
+        &mut self,
+        command: u8,
+        params: &[u8],
+        mut last: F,
+    ) -> Result<Vec<Frame>, UhfError>
+    where
+        F: FnMut(&Frame) -> bool,
+    {
+        if u16::try_from(params.len()).is_err() {
+            return Err(UhfError::InvalidParameter("Too many command parameters".into()));
+        }
+
+        let cmd = Self::create_command(command, params);
+        let irreversible =
+            matches!(command, Self::KILL_TAG | Self::LOCK_TAG | Self::BLOCK_PERMALOCK);
+        if irreversible {
+            self.guard_irreversible(&cmd)?;
+        }
+
+        let (frames, _) = self.exec_until(&cmd, &mut last)?;
+        if frames.is_empty() {
+            return Err(UhfError::Transport(format!(
+                "Timed out waiting for response to 0x{:02X}",
+                command
+            )));
         }
+        Ok(frames)
     }
 
     fn parse_tag(response: &[u8]) -> Result<Option<TagInfo>, UhfError> {
@@ -1344,7 +3078,7 @@ impl<T: RfidTransport> UhfRfid<T> {
 
         if response[0] == Self::HEADER && response[1] == Self::RESP_TYPE_TAG {
             let data_length = response[4] as usize;
-            let rssi = response[5];
+            let rssi = Rssi::from_raw(response[5]);
 
             let epc_start = 8;
             let epc_end = epc_start + data_length.saturating_sub(5);
@@ -1372,55 +3106,381 @@ impl<T: RfidTransport> UhfRfid<T> {
         }
     }
 
-    fn parse_firmware_version(response: &[u8]) -> Result<String, UhfError> {
-        if response.len() > 6 && response[0] == Self::HEADER && response[1] == Self::RESP_TYPE_NOTIFICATION {
-            let version_bytes = &response[6..response.len() - 2];
-            Ok(String::from_utf8_lossy(version_bytes).to_string())
-        } else {
-            Err(UhfError::InvalidResponse("Invalid firmware response".into()))
+    /// Whether a buffer data frame ends the dump: an empty frame or an error
+    fn is_buffer_end(frame: &[u8]) -> bool {
+        frame[2] == frame::ERROR_COMMAND || frame[3..6] == [0x00, 0x01, 0x00]
+    }
+
+    /// Parse the tag entries of one buffer data frame
+    fn parse_buffer_entries(
+        mut entries: &[u8],
+        reports: &mut Vec<TagReport>,
+    ) -> Result<(), UhfError> {
+        while !entries.is_empty() {
+            if entries.len() < 7 {
+                return Err(UhfError::InvalidResponse(format!(
+                    "Truncated buffer entry: {:02X?}",
+                    entries
+                )));
+            }
+
+            let pc = u16::from_be_bytes([entries[3], entries[4]]);
+            // PC bits 15-11 hold the EPC length in words
+            let epc_len = usize::from(pc >> 11) * 2;
+            let entry_len = 7 + epc_len;
+            if entries.len() < entry_len {
+                return Err(UhfError::InvalidResponse(format!(
+                    "Buffer entry with PC {:04X} needs {} bytes but only {} remain",
+                    pc,
+                    entry_len,
+                    entries.len()
+                )));
+            }
+
+            let crc_start = 5 + epc_len;
+            reports.push(TagReport {
+                epc: bytes_to_hex(&entries[5..crc_start]),
+                pc,
+                rssi: Rssi::from_raw(entries[2]),
+                crc: u16::from_be_bytes([entries[crc_start], entries[crc_start + 1]]),
+                read_count: u16::from_be_bytes([entries[0], entries[1]]),
+            });
+            entries = &entries[entry_len..];
+        }
+
+        Ok(())
+    }
+
+    fn parse_module_info(response: &[u8], info_type: u8) -> Result<String, UhfError> {
+        if response.len() < 8
+            || response[0] != Self::HEADER
+            || response[1] != Self::RESP_TYPE_NOTIFICATION
+            || response[2] != Self::GET_FIRMWARE
+        {
+            return Err(UhfError::InvalidResponse("Invalid module info response".into()));
+        }
+
+        if response[5] != info_type {
+            return Err(UhfError::InvalidResponse(format!(
+                "Module info type mismatch: requested 0x{:02X}, got 0x{:02X}",
+                info_type, response[5]
+            )));
+        }
+
+        // Parameter length covers the info type byte plus the ASCII string
+        let data_len = ((response[3] as usize) << 8) | (response[4] as usize);
+        let info_end = 5 + data_len;
+
+        if data_len == 0 || response.len() < info_end + 2 {
+            return Err(UhfError::InvalidResponse("Module info response too short".into()));
+        }
+
+        let info_bytes = &response[6..info_end];
+        Ok(String::from_utf8_lossy(info_bytes).trim_end_matches('\0').to_string())
+    }
+
+    /// Format a frame for logging, with passwords masked unless enabled
+    fn redacted<'a>(&self, frame: &'a [u8]) -> Redacted<'a> {
+        Redacted::new(frame).reveal(self.log_passwords)
+    }
+
+    /// Encode a command on the stack and write it to the transport
+    fn send_command(&mut self, command: u8, params: &[u8]) -> Result<(), UhfError> {
+        let mut cmd = [0u8; 64];
+        let len = frame::encode_command_into(command, params, &mut cmd)?;
+
+        self.wake_if_asleep()?;
+        self.transport
+            .clear_input()
+            .map_err(|e| UhfError::Transport(format!("{:?}", e)))?;
+        debug!("Sending command: {}", self.redacted(&cmd[..len]));
+        self.transport
+            .write(&cmd[..len])
+            .map_err(|e| UhfError::Transport(format!("{:?}", e)))?;
+        trace::dump(self.frame_dump, "tx", self.redacted(&cmd[..len]));
+        Ok(())
+    }
+
+    /// Send a command and receive its response into `rx`, in a command span
+    fn exec_frame<const N: usize>(
+        &mut self,
+        command: u8,
+        params: &[u8],
+        rx: &mut FrameBuffer<N>,
+    ) -> Result<Range<usize>, UhfError> {
+        let span = trace::command_span(command, params, self.log_passwords);
+        let _entered = span.enter();
+        let start = self.clock.now();
+
+        let result = self.exchange_frame(command, params, rx);
+        if let Ok(range) = &result {
+            trace::record_response(&span, rx.frame_at(range.clone()).as_bytes());
+        }
+        trace::record_duration(&span, self.elapsed_since(start));
+        result
+    }
+
+    /// Send a command and receive its response into `rx` without heap allocation
+    ///
+    /// Returns the position in `rx` of the first frame answering `command`: its
+    /// response, an error response or a tag notification. Other frames are dropped
+    /// rather than queued as notifications, since queueing them would allocate.
+    fn exchange_frame<const N: usize>(
+        &mut self,
+        command: u8,
+        params: &[u8],
+        rx: &mut FrameBuffer<N>,
+    ) -> Result<Range<usize>, UhfError> {
+        rx.clear();
+        self.send_command(command, params)?;
+
+        let start = self.clock.now();
+        let max_wait = self.response_timeout(command);
+
+        loop {
+            while let Some(range) = rx.next_frame_range() {
+                let frame = rx.frame_at(range.clone());
+                trace::dump(self.frame_dump, "rx", self.redacted(frame.as_bytes()));
+                if Self::answers(command, frame.as_bytes()) {
+                    self.last_activity = self.clock.now();
+                    return Ok(range);
+                }
+                debug!("Dropping unrelated frame: {}", self.redacted(frame.as_bytes()));
+            }
+
+            if self.elapsed_since(start) > max_wait {
+                return Err(UhfError::Transport("Timed out waiting for response".into()));
+            }
+
+            match rx.fill_from(&mut self.transport, 50) {
+                Ok(0) => self.clock.delay(Self::IDLE_POLL_INTERVAL),
+                Ok(_) => {}
+                Err(e) => return Err(UhfError::Transport(format!("{:?}", e))),
+            }
         }
     }
 
+    /// Whether a frame is the "no tag" error that ends an inventory
+    fn is_inventory_end(frame: &[u8]) -> bool {
+        frame.len() >= 8
+            && frame[1] == Self::RESP_TYPE_NOTIFICATION
+            && frame[2] == frame::ERROR_COMMAND
+            && frame[5] == 0x15
+    }
+
+    fn record_read(&mut self, epc: &str, rssi: Rssi) {
+        self.record_reads(epc, rssi, 1);
+    }
+
+    fn record_reads(&mut self, epc: &str, rssi: Rssi, count: usize) {
+        if let Some(stats) = self.stats.as_mut() {
+            stats.record_reads(epc, rssi, self.channel, count);
+        }
+    }
+
+    fn record_tag_ref(&mut self, tag: &TagRef<'_>) {
+        if self.stats.is_some() {
+            let tag = tag.to_tag_info();
+            self.record_read(&tag.epc, tag.rssi);
+        }
+    }
+
+    fn record_inventory(&mut self, rounds: u16, start: Duration, discarded: usize, ended: usize) {
+        let elapsed = self.elapsed_since(start);
+        if let Some(stats) = self.stats.as_mut() {
+            stats.rounds += u32::from(rounds);
+            stats.elapsed += elapsed;
+            stats.discarded_frames += discarded;
+            stats.end_notifications += ended;
+        }
+    }
+
+    /// Time elapsed since an earlier reading of the clock
+    fn elapsed_since(&self, start: Duration) -> Duration {
+        self.clock.now().saturating_sub(start)
+    }
+
     fn exec(&mut self, cmd: &[u8]) -> Result<Vec<u8>, UhfError> {
+        let (mut frames, leftover) = self.exec_until(cmd, |_| true)?;
+        Ok(if frames.is_empty() { leftover } else { frames.swap_remove(0).into_bytes() })
+    }
+
+    /// Send a command and collect the frames answering it until `last` accepts one
+    fn exec_until<F>(&mut self, cmd: &[u8], mut last: F) -> Result<Exchange, UhfError>
+    where
+        F: FnMut(&Frame) -> bool,
+    {
+        self.wake_if_asleep()?;
+
+        let mut response = self.exec_once(cmd, &mut last)?;
+        if response.0.is_empty() && response.1.is_empty() && self.auto_sleep.is_some() {
+            // The module may have gone to sleep on its own, in which case the
+            // first byte of the command only woke it up. Send it again.
+            debug!("No response with auto-sleep enabled, retrying command");
+            self.clock.delay(Self::WAKE_DELAY);
+            response = self.exec_once(cmd, &mut last)?;
+        }
+
+        self.last_activity = self.clock.now();
+        Ok(response)
+    }
+
+    /// Send a command and wait for the frames answering it, in a command span
+    fn exec_once<F>(&mut self, cmd: &[u8], last: &mut F) -> Result<Exchange, UhfError>
+    where
+        F: FnMut(&Frame) -> bool,
+    {
+        let span = trace::command_span(cmd[2], &cmd[5..cmd.len() - 2], self.log_passwords);
+        let _entered = span.enter();
+        let start = self.clock.now();
+
+        let result = self.exchange(cmd, last);
+        if let Ok((frames, _)) = &result
+            && let Some(response) = frames.first()
+        {
+            trace::record_response(&span, response.as_bytes());
+        }
+        trace::record_duration(&span, self.elapsed_since(start));
+        result
+    }
+
+    /// Send a command and wait for the frames answering it
+    ///
+    /// Reads until a complete frame for the command (or an error frame) arrives
+    /// that `last` accepts, or the command's deadline passes without a new answer.
+    /// Other frames received in the meantime are queued for
+    /// [`take_notifications`](Self::take_notifications). Returns the answering
+    /// frames and, if none arrived, whatever unparsed bytes were received.
+    fn exchange<F>(&mut self, cmd: &[u8], last: &mut F) -> Result<Exchange, UhfError>
+    where
+        F: FnMut(&Frame) -> bool,
+    {
+        let command = cmd[2];
+
         self.transport
             .clear_input()
             .map_err(|e| UhfError::Transport(format!("{:?}", e)))?;
-        debug!("Sending command: {:02X?}", cmd);
+        debug!("Sending command: {}", self.redacted(cmd));
         let written = self
             .transport
             .write(cmd)
             .map_err(|e| UhfError::Transport(format!("{:?}", e)))?;
         debug!("Wrote {} bytes", written);
-        std::thread::sleep(Duration::from_millis(200));
-
-        let mut response = vec![0u8; 100];
-        match self.transport.read(&mut response, 500) {
-            Ok(bytes_read) => {
-                response.truncate(bytes_read);
-                debug!("Received {} bytes: {:02X?}", bytes_read, response);
-                Ok(response)
+        trace::dump(self.frame_dump, "tx", self.redacted(cmd));
+
+        let timeout = self.response_timeout(command);
+        let mut start = self.clock.now();
+        let mut frames = Vec::new();
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; 256];
+
+        loop {
+            while let Some(frame) = Self::take_frame(&mut buffer, &mut self.rejected_frames) {
+                let bytes = frame.as_bytes();
+                trace::dump(self.frame_dump, "rx", self.redacted(bytes).answering(cmd));
+                if Self::answers(command, bytes) {
+                    let response = self.redacted(bytes).answering(cmd);
+                    debug!("Received {} bytes: {}", bytes.len(), response);
+                    let done = last(&frame);
+                    frames.push(frame);
+                    if done {
+                        return Ok((frames, Vec::new()));
+                    }
+                    // Every further frame gets the full deadline again
+                    start = self.clock.now();
+                    continue;
+                }
+                self.queue_notification(frame.into_bytes());
+            }
+
+            let elapsed = self.elapsed_since(start);
+            if elapsed >= timeout || buffer.len() >= Self::MAX_PENDING_BYTES {
+                break;
             }
-            Err(e) => {
-                error!("Read error: {:?}", e);
-                Err(UhfError::Transport(format!("{:?}", e)))
+
+            let remaining_ms = (timeout - elapsed).as_millis().clamp(1, u32::MAX as u128) as u32;
+            match self.transport.read(&mut chunk, remaining_ms) {
+                Ok(0) => self.clock.delay(Self::IDLE_POLL_INTERVAL),
+                Ok(bytes_read) => buffer.extend_from_slice(&chunk[..bytes_read]),
+                Err(e) => {
+                    error!("Read error: {:?}", e);
+                    return Err(UhfError::Transport(format!("{:?}", e)));
+                }
             }
         }
-    }
 
-    pub(crate) fn create_command(command: u8, params: &[u8]) -> Vec<u8> {
-        let param_len = params.len() as u16;
-        let msb = (param_len >> 8) as u8;
-        let lsb = (param_len & 0xFF) as u8;
+        if frames.is_empty() {
+            debug!(
+                "No complete response to 0x{:02X}, received {}",
+                command,
+                self.redacted(&buffer).answering(cmd)
+            );
+        }
+        Ok((frames, buffer))
+    }
 
-        let checksum = [Self::CMD_TYPE, command, msb, lsb]
+    fn response_timeout(&self, command: u8) -> Duration {
+        self.response_timeouts
             .iter()
-            .chain(params.iter())
-            .fold(0u8, |acc, &b| acc.wrapping_add(b));
+            .find(|(code, _)| *code == command)
+            .map_or(self.default_timeout, |(_, timeout)| *timeout)
+    }
+
+    /// Whether a received frame is the answer to the given command
+    fn answers(command: u8, frame: &[u8]) -> bool {
+        let answered = frame[2];
+        answered == command
+            || answered == frame::ERROR_COMMAND
+            // Select mode is acknowledged with the Select parameter command code
+            || (command == Self::SET_SELECT_MODE && answered == Self::SET_SELECT_PARAM)
+            // Write direction of BlockPermalock and QT answer with the next code
+            || (command == Self::BLOCK_PERMALOCK && answered == Self::BLOCK_PERMALOCK + 1)
+            || (command == Self::IMPINJ_MONZA_QT && answered == Self::IMPINJ_MONZA_QT + 1)
+            // Multiple poll reports its tags like a single poll
+            || (command == Self::MULTIPLE_POLL && answered == Self::SINGLE_POLL)
+    }
 
-        let mut cmd = vec![Self::HEADER, Self::CMD_TYPE, command, msb, lsb];
-        cmd.extend_from_slice(params);
-        cmd.push(checksum);
-        cmd.push(Self::END);
+    /// Split the first complete frame off the front of the receive buffer
+    ///
+    /// Frames are checked like in [`FrameBuffer`]: a candidate with a bad length,
+    /// checksum or end marker is counted in `rejected_frames` and its header byte
+    /// dropped, so it can never be taken for a response. Bytes before a valid frame
+    /// are dropped with it.
+    fn take_frame(buffer: &mut Vec<u8>, rejected_frames: &mut usize) -> Option<Frame> {
+        let mut search_from = 0;
+
+        while let Some(offset) = buffer[search_from..].iter().position(|&b| b == Self::HEADER) {
+            let frame_start = search_from + offset;
+            match frame::check_frame(&buffer[frame_start..], Self::MAX_PENDING_BYTES) {
+                FrameCheck::Incomplete => return None,
+                FrameCheck::Invalid => {
+                    // Not a frame start after all; resync on the next header
+                    *rejected_frames += 1;
+                    buffer.remove(frame_start);
+                    search_from = frame_start;
+                }
+                FrameCheck::Complete(frame_len) => {
+                    let frame = buffer[frame_start..frame_start + frame_len].to_vec();
+                    buffer.drain(..frame_start + frame_len);
+                    return Some(Frame::checked(frame));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn queue_notification(&mut self, frame: Vec<u8>) {
+        debug!("Queueing unrelated frame: {}", self.redacted(&frame));
+        if self.notifications.len() >= Self::MAX_QUEUED_NOTIFICATIONS {
+            self.notifications.pop_front();
+        }
+        self.notifications.push_back(frame);
+    }
+
+    pub(crate) fn create_command(command: u8, params: &[u8]) -> Vec<u8> {
+        let mut cmd = vec![0u8; params.len() + FRAME_OVERHEAD];
+        frame::encode_command_into(command, params, &mut cmd).expect("buffer sized for frame");
         cmd
     }
 }
@@ -0,0 +1,133 @@
+//! Masking of passwords in logged frames
+
+use core::fmt;
+use core::ops::Range;
+
+use crate::frame::{
+    BLOCK_PERMALOCK, CMD_TYPE, IMPINJ_MONZA_QT, KILL_TAG, LOCK_TAG, NXP_CHANGE_CONFIG,
+    NXP_CHANGE_EAS, NXP_READ_PROTECT, NXP_RESET_READ_PROTECT, READ_TAG_DATA, WRITE_TAG_DATA,
+};
+use crate::types::MemoryBank;
+
+/// Memory bank code of the Reserved bank
+const RESERVED_BANK: u8 = MemoryBank::Reserved as u8;
+/// Offset of the parameters in a frame: header, type, command and length
+const PARAMS_OFFSET: usize = 5;
+/// Parameter offsets of the memory bank and the written data in read/write commands
+const BANK_PARAM: usize = 4;
+const WRITE_DATA_PARAM: usize = 7;
+
+/// Parameter bytes of a command that hold an access or kill password
+pub(crate) fn password_params(command: u8) -> Option<Range<usize>> {
+    match command {
+        READ_TAG_DATA | WRITE_TAG_DATA | LOCK_TAG | KILL_TAG | BLOCK_PERMALOCK
+        | NXP_CHANGE_CONFIG | NXP_READ_PROTECT | NXP_RESET_READ_PROTECT | NXP_CHANGE_EAS
+        | IMPINJ_MONZA_QT => Some(0..4),
+        _ => None,
+    }
+}
+
+/// A frame formatted like `{:02X?}`, with passwords masked as `**`
+///
+/// Masks the access or kill password of every command carrying one and the data
+/// of writes to the Reserved bank. The data of responses to Reserved bank reads is
+/// masked when the request is known, see [`answering`](Self::answering). Use it wherever
+/// frames are logged or recorded, e.g. in a transport that keeps a trace of the
+/// traffic.
+#[derive(Clone)]
+pub struct Redacted<'a> {
+    bytes: &'a [u8],
+    /// Position of the parameters in `bytes`
+    params: Range<usize>,
+    frame_type: u8,
+    command: u8,
+    /// Whether the frame answers a read of the Reserved bank
+    reserved_read: bool,
+    reveal: bool,
+}
+
+impl<'a> Redacted<'a> {
+    /// Format a frame, or the bytes received so far of one
+    pub fn new(frame: &'a [u8]) -> Self {
+        // Trust the length field so that partly received frames are masked too
+        let params_len = match frame {
+            [_, _, _, msb, lsb, ..] => usize::from(u16::from_be_bytes([*msb, *lsb])),
+            _ => 0,
+        };
+        let params_end = (PARAMS_OFFSET + params_len).min(frame.len());
+        Self {
+            bytes: frame,
+            params: PARAMS_OFFSET.min(params_end)..params_end,
+            frame_type: frame.get(1).copied().unwrap_or(CMD_TYPE),
+            command: frame.get(2).copied().unwrap_or(0),
+            reserved_read: false,
+            reveal: false,
+        }
+    }
+
+    /// Format the parameters of a command
+    #[cfg(feature = "tracing")]
+    pub(crate) fn params(command: u8, params: &'a [u8]) -> Self {
+        Self {
+            bytes: params,
+            params: 0..params.len(),
+            frame_type: CMD_TYPE,
+            command,
+            reserved_read: false,
+            reveal: false,
+        }
+    }
+
+    /// Name the command the frame answers, so data read from the Reserved bank is masked
+    pub fn answering(mut self, request: &[u8]) -> Self {
+        self.reserved_read = request.get(2) == Some(&READ_TAG_DATA)
+            && request.get(PARAMS_OFFSET + BANK_PARAM) == Some(&RESERVED_BANK);
+        self
+    }
+
+    /// Show passwords in plain text, for lab use
+    pub fn reveal(mut self, reveal: bool) -> Self {
+        self.reveal = reveal;
+        self
+    }
+
+    fn is_secret(&self, index: usize) -> bool {
+        if self.reveal || !self.params.contains(&index) {
+            return false;
+        }
+        let param = index - self.params.start;
+
+        if self.frame_type != CMD_TYPE {
+            // Read responses carry UL and the tag's PC+EPC ahead of the data
+            let data_param = self.bytes.get(self.params.start).map_or(0, |ul| 1 + usize::from(*ul));
+            return self.reserved_read && self.command == READ_TAG_DATA && param >= data_param;
+        }
+        let reserved_write = self.command == WRITE_TAG_DATA
+            && self.bytes.get(self.params.start + BANK_PARAM) == Some(&RESERVED_BANK)
+            && param >= WRITE_DATA_PARAM;
+        reserved_write || password_params(self.command).is_some_and(|range| range.contains(&param))
+    }
+}
+
+impl fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for (index, byte) in self.bytes.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            if self.is_secret(index) {
+                f.write_str("**")?;
+            } else {
+                write!(f, "{:02X}", byte)?;
+            }
+        }
+        f.write_str("]")
+    }
+}
+
+impl fmt::Debug for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
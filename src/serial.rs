@@ -17,6 +17,11 @@ impl SerialTransport {
 
         Ok(Self { port })
     }
+
+    /// Wrap an already opened and configured port
+    pub fn from_port(port: Box<dyn serialport::SerialPort>) -> Self {
+        Self { port }
+    }
 }
 
 impl RfidTransport for SerialTransport {
@@ -38,4 +43,15 @@ impl RfidTransport for SerialTransport {
             .clear(serialport::ClearBuffer::Input)
             .map_err(|e| std::io::Error::other(e))
     }
+
+    fn set_host_baud(&mut self, baud_rate: u32) -> Result<bool, Self::Error> {
+        self.port
+            .set_baud_rate(baud_rate)
+            .map_err(std::io::Error::other)?;
+        Ok(true)
+    }
+
+    fn host_baud(&self) -> Option<u32> {
+        self.port.baud_rate().ok()
+    }
 }
@@ -0,0 +1,94 @@
+//! Command spans and frame dumps
+//!
+//! With the `tracing` feature every command exchange runs in a `command` span and
+//! frame dumps are `tracing` events. Without it spans compile to nothing and frame
+//! dumps go to `log` at trace level.
+
+use core::time::Duration;
+
+#[cfg(feature = "tracing")]
+use crate::frame;
+use crate::redact::Redacted;
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::Span;
+
+/// Stand-in for `tracing::Span` without the `tracing` feature
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Span;
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Entered;
+
+#[cfg(not(feature = "tracing"))]
+impl Span {
+    pub(crate) fn enter(&self) -> Entered {
+        Entered
+    }
+}
+
+/// Open the span of one command exchange
+///
+/// The span carries the command code and its parameters, with passwords masked
+/// unless `reveal` is set.
+/// Response type, error code and duration are recorded when the exchange ends.
+/// Streaming inventories do not enter it, so their tag counts end up on the span
+/// of the reader method, see [`record_tags`].
+#[cfg(feature = "tracing")]
+pub(crate) fn command_span(command: u8, params: &[u8], reveal: bool) -> Span {
+    tracing::debug_span!(
+        "command",
+        command = format_args!("0x{:02X}", command),
+        params = %Redacted::params(command, params).reveal(reveal),
+        response_type = tracing::field::Empty,
+        error_code = tracing::field::Empty,
+        duration_us = tracing::field::Empty,
+    )
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn command_span(_command: u8, _params: &[u8], _reveal: bool) -> Span {
+    Span
+}
+
+/// Record the frame answering the command of a span
+#[cfg(feature = "tracing")]
+pub(crate) fn record_response(span: &Span, response: &[u8]) {
+    span.record("response_type", format_args!("0x{:02X}", response[1]));
+    if response[2] == frame::ERROR_COMMAND && response.len() > 5 {
+        span.record("error_code", format_args!("0x{:02X}", response[5]));
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn record_response(_span: &Span, _response: &[u8]) {}
+
+/// Record how long the exchange of a span took
+#[cfg(feature = "tracing")]
+pub(crate) fn record_duration(span: &Span, duration: Duration) {
+    span.record("duration_us", duration.as_micros() as u64);
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn record_duration(_span: &Span, _duration: Duration) {}
+
+/// Record the number of tags an inventory method reported on its span
+#[cfg(feature = "tracing")]
+pub(crate) fn record_tags(count: usize) {
+    Span::current().record("tags", count as u64);
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn record_tags(_count: usize) {}
+
+/// Dump a frame sent to ("tx") or received from ("rx") the module
+pub(crate) fn dump(enabled: bool, direction: &str, frame: Redacted<'_>) {
+    if !enabled {
+        return;
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::trace!(direction, frame = %frame);
+    #[cfg(not(feature = "tracing"))]
+    log::trace!("{} {}", direction, frame);
+}
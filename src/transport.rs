@@ -2,7 +2,7 @@
 /// Implement this trait for different transports (UART, serial port, etc.)
 pub trait RfidTransport {
     /// Error type for transport operations
-    type Error: std::fmt::Debug;
+    type Error: core::fmt::Debug;
 
     /// Write data to the transport
     fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error>;
@@ -12,4 +12,16 @@ pub trait RfidTransport {
 
     /// Clear the input buffer
     fn clear_input(&mut self) -> Result<(), Self::Error>;
+
+    /// Change the host side baud rate
+    ///
+    /// Returns `false` if the transport cannot change its baud rate, which is the default.
+    fn set_host_baud(&mut self, _baud_rate: u32) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+
+    /// Current host side baud rate, if the transport knows it
+    fn host_baud(&self) -> Option<u32> {
+        None
+    }
 }
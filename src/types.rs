@@ -1,10 +1,113 @@
 //! Types for RFID operations
 
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
 /// Information about a detected RFID tag
 #[derive(Debug, Clone)]
 pub struct TagInfo {
     pub epc: String,
-    pub rssi: u8,
+    pub rssi: Rssi,
+}
+
+/// A tag entry from the reader's inventory buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagReport {
+    /// EPC as uppercase hex
+    pub epc: String,
+    /// Protocol control word
+    pub pc: u16,
+    /// Signal strength recorded by the module
+    pub rssi: Rssi,
+    /// CRC-16 sent by the tag
+    pub crc: u16,
+    /// How often the tag was read during the inventory
+    pub read_count: u16,
+}
+
+impl From<TagReport> for TagInfo {
+    fn from(report: TagReport) -> Self {
+        Self {
+            epc: report.epc,
+            rssi: report.rssi,
+        }
+    }
+}
+
+/// Signal strength of a tag reply
+///
+/// The module reports RSSI as a signed dBm value in one byte, so 0xC9 is -55 dBm.
+/// Values order by signal strength.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Rssi(u8);
+
+impl Rssi {
+    /// RSSI from the raw byte reported by the module
+    pub const fn from_raw(raw: u8) -> Self {
+        Self(raw)
+    }
+
+    /// RSSI for a signal strength in dBm
+    pub const fn from_dbm(dbm: i8) -> Self {
+        Self(dbm as u8)
+    }
+
+    /// Raw byte as reported by the module
+    pub const fn raw(self) -> u8 {
+        self.0
+    }
+
+    /// Signal strength in dBm
+    pub const fn dbm(self) -> i8 {
+        self.0 as i8
+    }
+}
+
+impl From<u8> for Rssi {
+    fn from(raw: u8) -> Self {
+        Self::from_raw(raw)
+    }
+}
+
+impl Ord for Rssi {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.dbm().cmp(&other.dbm())
+    }
+}
+
+impl PartialOrd for Rssi {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl core::fmt::Display for Rssi {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} dBm", self.dbm())
+    }
+}
+
+/// Identification strings reported by the reader module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleInfo {
+    /// Hardware version (e.g. "M100 V1.00")
+    pub hardware_version: String,
+    /// Firmware (software) version
+    pub firmware_version: String,
+    /// Manufacturer string
+    pub manufacturer: String,
+}
+
+impl core::fmt::Display for ModuleInfo {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} (hardware {}, firmware {})",
+            self.manufacturer, self.hardware_version, self.firmware_version
+        )
+    }
 }
 
 /// Memory bank selection for tag operations
@@ -130,6 +233,17 @@ impl Region {
         }
     }
 
+    /// Get the number of channels available in this region
+    pub fn channel_count(&self) -> u8 {
+        match self {
+            Region::China900 => 20,
+            Region::Us => 52,
+            Region::Europe => 15,
+            Region::China800 => 20,
+            Region::Korea => 32,
+        }
+    }
+
     /// Calculate channel index from frequency
     pub fn channel_from_frequency(&self, freq_mhz: f64) -> u8 {
         ((freq_mhz - self.base_frequency()) / self.channel_spacing()) as u8
@@ -156,6 +270,191 @@ impl TryFrom<u8> for Region {
     }
 }
 
+/// Custom frequency-hopping table for a region
+///
+/// The channel list is validated against the region's band on construction,
+/// so a plan can always be programmed with `UhfRfid::set_hopping_plan()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HoppingPlan {
+    region: Region,
+    channels: Vec<u8>,
+}
+
+impl HoppingPlan {
+    /// Create a plan from channel indices
+    pub fn from_channels(region: Region, channels: &[u8]) -> Result<Self, UhfError> {
+        if channels.is_empty() {
+            return Err(UhfError::InvalidParameter(
+                "Hopping plan must contain at least one channel".into(),
+            ));
+        }
+
+        for (i, &channel) in channels.iter().enumerate() {
+            if channel >= region.channel_count() {
+                return Err(UhfError::InvalidParameter(format!(
+                    "Channel {} is outside the {:?} band (0-{})",
+                    channel,
+                    region,
+                    region.channel_count() - 1
+                )));
+            }
+            if channels[..i].contains(&channel) {
+                return Err(UhfError::InvalidParameter(format!(
+                    "Channel {} appears more than once in hopping plan",
+                    channel
+                )));
+            }
+        }
+
+        Ok(Self {
+            region,
+            channels: channels.to_vec(),
+        })
+    }
+
+    /// Create a plan from channel frequencies in MHz
+    ///
+    /// Each frequency must lie on the region's channel grid.
+    pub fn from_frequencies(region: Region, freqs_mhz: &[f64]) -> Result<Self, UhfError> {
+        let mut channels = Vec::with_capacity(freqs_mhz.len());
+
+        for &freq in freqs_mhz {
+            let offset = (freq - region.base_frequency()) / region.channel_spacing();
+            // Round to nearest without f64::round, which needs std; offsets below
+            // -0.5 end up off-grid and are rejected below
+            let index = (offset + 0.5) as i64 as f64;
+
+            if (offset - index).abs() > 1e-3 || index < 0.0 || index >= region.channel_count() as f64 {
+                return Err(UhfError::InvalidParameter(format!(
+                    "{} MHz is not a valid {:?} channel frequency",
+                    freq, region
+                )));
+            }
+
+            channels.push(index as u8);
+        }
+
+        Self::from_channels(region, &channels)
+    }
+
+    /// Region this plan was validated against
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    /// Channel indices in hopping order
+    pub fn channels(&self) -> &[u8] {
+        &self.channels
+    }
+
+    /// Channel frequencies in MHz
+    pub fn frequencies(&self) -> Vec<f64> {
+        self.channels
+            .iter()
+            .map(|&channel| self.region.frequency_from_channel(channel))
+            .collect()
+    }
+}
+
+/// Per-channel signal levels measured by the reader
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpectrumReport {
+    /// Region the channels belong to
+    pub region: Region,
+    /// Index of the first measured channel
+    pub start_channel: u8,
+    /// Level in dBm for each channel, starting at `start_channel`
+    pub levels_dbm: Vec<i8>,
+}
+
+impl SpectrumReport {
+    /// Level in dBm measured on a channel, if it was part of the scan
+    pub fn level(&self, channel: u8) -> Option<i8> {
+        channel
+            .checked_sub(self.start_channel)
+            .and_then(|offset| self.levels_dbm.get(offset as usize))
+            .copied()
+    }
+
+    /// Channel indices paired with their level in dBm
+    pub fn channels(&self) -> impl Iterator<Item = (u8, i8)> + '_ {
+        self.levels_dbm
+            .iter()
+            .enumerate()
+            .map(|(i, &level)| (self.start_channel + i as u8, level))
+    }
+
+    /// Channel with the lowest level, suitable for `UhfRfid::set_channel()`
+    pub fn quietest_channel(&self) -> Option<u8> {
+        self.quietest_channels(1).first().copied()
+    }
+
+    /// Up to `count` channels ordered from quietest to loudest
+    ///
+    /// Channels with equal levels keep their index order.
+    pub fn quietest_channels(&self, count: usize) -> Vec<u8> {
+        let mut channels: Vec<(u8, i8)> = self.channels().collect();
+        channels.sort_by_key(|&(_, level)| level);
+        channels.into_iter().take(count).map(|(channel, _)| channel).collect()
+    }
+
+    /// Hopping plan over the `count` quietest channels
+    pub fn hopping_plan(&self, count: usize) -> Result<HoppingPlan, UhfError> {
+        HoppingPlan::from_channels(self.region, &self.quietest_channels(count))
+    }
+}
+
+/// Serial baud rate of the reader module
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BaudRate {
+    /// 9600 bps
+    B9600,
+    /// 19200 bps
+    B19200,
+    /// 38400 bps
+    B38400,
+    /// 57600 bps
+    B57600,
+    /// 115200 bps (module default)
+    #[default]
+    B115200,
+}
+
+impl BaudRate {
+    /// All supported rates, slowest first
+    pub const ALL: [BaudRate; 5] = [
+        BaudRate::B9600,
+        BaudRate::B19200,
+        BaudRate::B38400,
+        BaudRate::B57600,
+        BaudRate::B115200,
+    ];
+
+    /// Rate in bits per second
+    pub fn bps(&self) -> u32 {
+        match self {
+            BaudRate::B9600 => 9600,
+            BaudRate::B19200 => 19200,
+            BaudRate::B38400 => 38400,
+            BaudRate::B57600 => 57600,
+            BaudRate::B115200 => 115200,
+        }
+    }
+
+    /// Encode to protocol format (rate / 100, big-endian)
+    pub fn to_bytes(&self) -> [u8; 2] {
+        ((self.bps() / 100) as u16).to_be_bytes()
+    }
+}
+
+impl TryFrom<u32> for BaudRate {
+    type Error = ();
+
+    fn try_from(bps: u32) -> Result<Self, Self::Error> {
+        Self::ALL.into_iter().find(|rate| rate.bps() == bps).ok_or(())
+    }
+}
+
 /// Query parameters for tag inventory (per EPC Gen2)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct QueryParams {
@@ -221,6 +520,338 @@ impl QueryParams {
     }
 }
 
+/// Settings for [`UhfRfid::adaptive_inventory`](crate::UhfRfid::adaptive_inventory)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveQConfig {
+    /// Q value for the first burst
+    pub initial_q: u8,
+    /// Lowest Q the algorithm may choose
+    pub min_q: u8,
+    /// Highest Q the algorithm may choose (at most 15)
+    pub max_q: u8,
+    /// Inventory rounds per `multiple_poll` burst
+    pub rounds_per_burst: u16,
+    /// Number of bursts to run
+    pub bursts: u16,
+}
+
+impl Default for AdaptiveQConfig {
+    fn default() -> Self {
+        Self {
+            initial_q: 4,
+            min_q: 0,
+            max_q: 15,
+            rounds_per_burst: 10,
+            bursts: 10,
+        }
+    }
+}
+
+/// Change made to Q after a burst
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QAdjustment {
+    /// Q kept as is
+    Hold,
+    /// Q raised because more tags answered than there were slots
+    Increase,
+    /// Q lowered because most slots stayed empty
+    Decrease,
+    /// Q set back because reads per round dropped after the previous change
+    Revert,
+}
+
+/// What the adaptive-Q algorithm observed in one burst and what it decided
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QDecision {
+    /// Burst number, starting at 0
+    pub burst: u16,
+    /// Q used during the burst
+    pub q: u8,
+    /// Tag reads in the burst, including repeats
+    pub reads: usize,
+    /// Distinct tags read in the burst
+    pub unique_tags: usize,
+    /// Average reads per inventory round
+    pub reads_per_round: f32,
+    /// Reads per second over the burst
+    pub reads_per_second: f32,
+    /// Change applied for the next burst
+    pub adjustment: QAdjustment,
+    /// Q for the next burst
+    pub next_q: u8,
+}
+
+/// Adaptive Q state machine driven by per-burst read statistics
+///
+/// The module does not report per-slot collision or empty counts, so the tag
+/// population is estimated from the distinct tags read in each burst. Q moves one
+/// step per burst towards the value whose 2^Q slots match that population, which
+/// keeps it steady while tag counts fluctuate between bursts.
+///
+/// The read rate checks each step: if reads per round drop in the burst after Q
+/// changed, Q is reverted and not tried again until the estimated population moves.
+#[derive(Debug, Clone)]
+pub struct AdaptiveQ {
+    config: AdaptiveQConfig,
+    q: u8,
+    burst: u16,
+    /// Q and reads per round of the burst before the last change
+    changed_from: Option<(u8, f32)>,
+    /// Q that was reverted and the population target it was tried for
+    rejected: Option<(u8, u8)>,
+}
+
+impl AdaptiveQ {
+    pub fn new(config: AdaptiveQConfig) -> Result<Self, UhfError> {
+        if config.max_q > 15 || config.min_q > config.max_q {
+            return Err(UhfError::InvalidParameter(
+                "Adaptive Q bounds must satisfy min_q <= max_q <= 15".into(),
+            ));
+        }
+        if !(config.min_q..=config.max_q).contains(&config.initial_q) {
+            return Err(UhfError::InvalidParameter(
+                "Initial Q must lie between min_q and max_q".into(),
+            ));
+        }
+        if config.rounds_per_burst == 0 {
+            return Err(UhfError::InvalidParameter("Rounds per burst must be at least 1".into()));
+        }
+
+        Ok(Self {
+            config,
+            q: config.initial_q,
+            burst: 0,
+            changed_from: None,
+            rejected: None,
+        })
+    }
+
+    /// Q to use for the next burst
+    pub fn q(&self) -> u8 {
+        self.q
+    }
+
+    /// Record the outcome of a burst and choose Q for the next one
+    pub fn observe(&mut self, reads: usize, unique_tags: usize, elapsed: core::time::Duration) -> QDecision {
+        // Smallest Q with at least one slot per tag; no tags means every slot was empty
+        let target = match unique_tags {
+            0 => self.config.min_q,
+            n => (usize::BITS - (n - 1).leading_zeros()).min(15) as u8,
+        }
+        .clamp(self.config.min_q, self.config.max_q);
+        let reads_per_round = reads as f32 / self.config.rounds_per_burst as f32;
+
+        if self.rejected.is_some_and(|(_, rejected_for)| rejected_for != target) {
+            self.rejected = None;
+        }
+
+        let (adjustment, next_q) = match self.changed_from.take() {
+            Some((previous_q, previous_rate)) if reads_per_round < previous_rate => {
+                self.rejected = Some((self.q, target));
+                (QAdjustment::Revert, previous_q)
+            }
+            _ => {
+                let step = if target > self.q {
+                    Some((QAdjustment::Increase, self.q + 1))
+                } else if target < self.q {
+                    Some((QAdjustment::Decrease, self.q - 1))
+                } else {
+                    None
+                };
+                match step {
+                    Some((_, q)) if self.rejected.is_some_and(|(rejected, _)| rejected == q) => {
+                        (QAdjustment::Hold, self.q)
+                    }
+                    Some(step) => step,
+                    None => (QAdjustment::Hold, self.q),
+                }
+            }
+        };
+        if matches!(adjustment, QAdjustment::Increase | QAdjustment::Decrease) {
+            self.changed_from = Some((self.q, reads_per_round));
+        }
+
+        let seconds = elapsed.as_secs_f32();
+        let decision = QDecision {
+            burst: self.burst,
+            q: self.q,
+            reads,
+            unique_tags,
+            reads_per_round,
+            reads_per_second: if seconds > 0.0 { reads as f32 / seconds } else { 0.0 },
+            adjustment,
+            next_q,
+        };
+
+        self.q = next_q;
+        self.burst += 1;
+        decision
+    }
+}
+
+/// Result of [`UhfRfid::adaptive_inventory`](crate::UhfRfid::adaptive_inventory)
+#[derive(Debug, Clone)]
+pub struct AdaptiveInventory {
+    /// Distinct tags read over all bursts, with the RSSI of the latest read
+    pub tags: Vec<TagInfo>,
+    /// Per-burst observations and Q decisions
+    pub decisions: Vec<QDecision>,
+}
+
+/// How [`UhfRfid::run_inventory`](crate::UhfRfid::run_inventory) uses the session flags
+///
+/// Every tag keeps an inventoried flag (A or B) per session, and a successful read flips
+/// it. S2 and S3 flags persist for a while after the tag loses power, which keeps tags
+/// that were already read quiet so weaker ones get a chance to reply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryStrategy {
+    /// Reset all flags to A, then inventory target A until a burst reads no tags
+    SingleTarget { session: QuerySession },
+    /// Reset all flags to A, then inventory target A until quiet, flip to target B until
+    /// quiet, and keep flipping until both sides are quiet in a row
+    DualTarget { session: QuerySession },
+    /// Inventory target A without resetting flags, so tags read by an earlier run stay
+    /// suppressed until their flag decays and only new arrivals are reported
+    SingleTargetWithSuppression { session: QuerySession },
+}
+
+impl InventoryStrategy {
+    /// Session whose flags the strategy works on
+    pub fn session(&self) -> QuerySession {
+        match *self {
+            Self::SingleTarget { session }
+            | Self::DualTarget { session }
+            | Self::SingleTargetWithSuppression { session } => session,
+        }
+    }
+
+    /// Whether the session flags are reset to A before the first burst
+    pub fn resets_flags(&self) -> bool {
+        !matches!(self, Self::SingleTargetWithSuppression { .. })
+    }
+}
+
+impl Default for InventoryStrategy {
+    fn default() -> Self {
+        Self::DualTarget {
+            session: QuerySession::S2,
+        }
+    }
+}
+
+/// Signal strength filters for [`UhfRfid::inventory`](crate::UhfRfid::inventory)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InventoryOptions {
+    /// Ignore reads weaker than this
+    pub min_rssi: Option<Rssi>,
+    /// Report only this many tags, strongest first
+    pub strongest: Option<usize>,
+}
+
+impl InventoryOptions {
+    /// Whether a single read passes the RSSI filter
+    pub fn accepts(&self, tag: &TagInfo) -> bool {
+        self.min_rssi.is_none_or(|min| tag.rssi >= min)
+    }
+
+    /// Reduce reads to the distinct tags that pass the filters
+    ///
+    /// Each tag keeps its strongest read. With `strongest` set the tags are sorted
+    /// strongest first and truncated, otherwise they stay in order of first read.
+    pub fn apply(&self, reads: impl IntoIterator<Item = TagInfo>) -> Vec<TagInfo> {
+        let mut tags: Vec<TagInfo> = Vec::new();
+        for tag in reads.into_iter().filter(|tag| self.accepts(tag)) {
+            match tags.iter_mut().find(|known| **known == tag) {
+                Some(known) => known.rssi = known.rssi.max(tag.rssi),
+                None => tags.push(tag),
+            }
+        }
+
+        if let Some(count) = self.strongest {
+            tags.sort_by_key(|tag| core::cmp::Reverse(tag.rssi));
+            tags.truncate(count);
+        }
+        tags
+    }
+}
+
+/// Inventory performance counters, see [`UhfRfid::enable_inventory_stats`] and
+/// [`UhfRfid::with_inventory_stats`]
+///
+/// Per-channel counts need a fixed channel. The module does not report the channel
+/// of a read, so with frequency hopping enabled (the module default) reads cannot be
+/// attributed and `channel_reads` stays empty. Disable hopping and set the channel,
+/// e.g. once per channel of a scan, to fill it.
+///
+/// [`UhfRfid::enable_inventory_stats`]: crate::UhfRfid::enable_inventory_stats
+/// [`UhfRfid::with_inventory_stats`]: crate::UhfRfid::with_inventory_stats
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InventoryStats {
+    /// Inventory rounds requested by round-limited calls (`single_poll` counts one);
+    /// `poll_for_duration` runs until stopped and adds no rounds
+    pub rounds: u32,
+    /// Tag reads, including repeated reads of the same tag
+    pub reads: usize,
+    /// Time spent in inventory calls
+    pub elapsed: core::time::Duration,
+    /// Tag reads per channel index; reads while hopping or before the channel was set
+    /// or read are not counted
+    pub channel_reads: BTreeMap<u8, usize>,
+    /// Tag reads per RSSI value
+    pub rssi_histogram: BTreeMap<Rssi, usize>,
+    /// Frames rejected by the parser (truncated, bad checksum or missing end marker)
+    pub discarded_frames: usize,
+    /// "No tag" (error 0x15) notifications that ended an inventory
+    pub end_notifications: usize,
+    epcs: BTreeSet<String>,
+}
+
+impl InventoryStats {
+    /// Number of distinct EPCs read
+    pub fn unique_tags(&self) -> usize {
+        self.epcs.len()
+    }
+
+    /// Tag reads per second of inventory time
+    pub fn reads_per_second(&self) -> f32 {
+        let seconds = self.elapsed.as_secs_f32();
+        if seconds > 0.0 { self.reads as f32 / seconds } else { 0.0 }
+    }
+
+    /// Add the counters of `other`, e.g. those of a single call
+    pub(crate) fn merge(&mut self, other: &InventoryStats) {
+        self.rounds += other.rounds;
+        self.reads += other.reads;
+        self.elapsed += other.elapsed;
+        for (&channel, &count) in &other.channel_reads {
+            *self.channel_reads.entry(channel).or_default() += count;
+        }
+        for (&rssi, &count) in &other.rssi_histogram {
+            *self.rssi_histogram.entry(rssi).or_default() += count;
+        }
+        self.discarded_frames += other.discarded_frames;
+        self.end_notifications += other.end_notifications;
+        self.epcs.extend(other.epcs.iter().cloned());
+    }
+
+    pub(crate) fn record_reads(
+        &mut self,
+        epc: &str,
+        rssi: Rssi,
+        channel: Option<u8>,
+        count: usize,
+    ) {
+        self.reads += count;
+        *self.rssi_histogram.entry(rssi).or_default() += count;
+        if let Some(channel) = channel {
+            *self.channel_reads.entry(channel).or_default() += count;
+        }
+        if !self.epcs.contains(epc) {
+            self.epcs.insert(epc.into());
+        }
+    }
+}
+
 /// Sel field for Query command
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[repr(u8)]
@@ -254,6 +885,27 @@ pub enum QueryTarget {
     B = 0x01,
 }
 
+impl QueryTarget {
+    /// The other inventoried flag value
+    pub fn flipped(self) -> Self {
+        match self {
+            Self::A => Self::B,
+            Self::B => Self::A,
+        }
+    }
+}
+
+impl From<QuerySession> for SelectTarget {
+    fn from(session: QuerySession) -> Self {
+        match session {
+            QuerySession::S0 => Self::S0,
+            QuerySession::S1 => Self::S1,
+            QuerySession::S2 => Self::S2,
+            QuerySession::S3 => Self::S3,
+        }
+    }
+}
+
 impl PartialEq for TagInfo {
     fn eq(&self, other: &Self) -> bool {
         self.epc == other.epc
@@ -338,6 +990,208 @@ impl LockPayload {
     }
 }
 
+/// Lock state of a tag memory area as observed by probing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockState {
+    /// Accessible without a password (unlocked or permanently unlocked)
+    Unlocked,
+    /// Accessible only with the access password
+    Locked,
+    /// Not accessible even with the access password
+    PermaLocked,
+    /// The area does not exist or the probe was inconclusive
+    Unknown,
+}
+
+/// Lock state of every lock area
+///
+/// Returned by [`UhfRfid::probe_lock_state`](crate::UhfRfid::probe_lock_state) and
+/// [`UhfRfid::probe_lock_state_with_writes`](crate::UhfRfid::probe_lock_state_with_writes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockStatus {
+    /// Read/write lock of the kill password
+    pub kill_password: LockState,
+    /// Read/write lock of the access password
+    pub access_password: LockState,
+    /// Write lock of the EPC bank
+    pub epc: LockState,
+    /// Write lock of the TID bank
+    pub tid: LockState,
+    /// Write lock of the User bank
+    pub user: LockState,
+}
+
+impl LockStatus {
+    /// State of one lock area
+    pub fn get(&self, target: LockTarget) -> LockState {
+        match target {
+            LockTarget::User => self.user,
+            LockTarget::Tid => self.tid,
+            LockTarget::Epc => self.epc,
+            LockTarget::AccessPassword => self.access_password,
+            LockTarget::KillPassword => self.kill_password,
+        }
+    }
+}
+
+/// How irreversible commands are executed
+///
+/// Irreversible commands are [`kill_tag`](crate::UhfRfid::kill_tag), permanent
+/// [`lock_tag`](crate::UhfRfid::lock_tag) actions and
+/// [`block_permalock`](crate::UhfRfid::block_permalock).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GuardMode {
+    /// Send irreversible commands without checks
+    #[default]
+    Off,
+    /// Require a [`Confirmation`] and check that only the confirmed tag is in the field
+    Guarded,
+    /// Run the guarded checks, then log the command frame instead of sending it and
+    /// fail with [`UhfError::DryRun`]
+    DryRun,
+}
+
+/// Single-use confirmation naming the tag an irreversible command is meant for
+///
+/// See [`UhfRfid::confirm`](crate::UhfRfid::confirm).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Confirmation {
+    /// The tag with this EPC (hex, case-insensitive)
+    Epc(String),
+    /// The tag whose TID starts with these bytes (whole words)
+    Tid(Vec<u8>),
+}
+
+/// 32-bit access or kill password
+///
+/// Parses from eight hex digits with an optional `0x` prefix. The all-zero password
+/// means "no password": the tag needs no access password, and a zero kill password
+/// cannot kill it.
+///
+/// `Debug` masks the value as `Password(********)` so passwords stay out of logs and
+/// panic messages. `Display` shows the eight hex digits; use it only where the
+/// password is meant to be seen.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Password([u8; 4]);
+
+impl Password {
+    /// The all-zero password
+    pub const ZERO: Self = Self([0; 4]);
+
+    /// Password from its bytes, most significant first
+    pub const fn new(bytes: [u8; 4]) -> Self {
+        Self(bytes)
+    }
+
+    /// Bytes as sent to the module
+    pub const fn as_bytes(&self) -> &[u8; 4] {
+        &self.0
+    }
+
+    /// Password as a 32-bit value
+    pub const fn to_u32(self) -> u32 {
+        u32::from_be_bytes(self.0)
+    }
+
+    /// Whether this is the all-zero password
+    pub const fn is_zero(self) -> bool {
+        self.to_u32() == 0
+    }
+}
+
+impl From<[u8; 4]> for Password {
+    fn from(bytes: [u8; 4]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<u32> for Password {
+    fn from(value: u32) -> Self {
+        Self(value.to_be_bytes())
+    }
+}
+
+impl core::str::FromStr for Password {
+    type Err = UhfError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+        if digits.len() != 8 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(UhfError::InvalidParameter(format!(
+                "Password must be 8 hex digits, got \"{}\"",
+                s
+            )));
+        }
+        u32::from_str_radix(digits, 16)
+            .map(Self::from)
+            .map_err(|_| UhfError::InvalidParameter(format!("Invalid password \"{}\"", s)))
+    }
+}
+
+impl core::fmt::Display for Password {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:08X}", self.to_u32())
+    }
+}
+
+impl core::fmt::Debug for Password {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Password(********)")
+    }
+}
+
+/// Site-wide secret from which per-tag passwords are derived
+///
+/// Each password is the first non-zero 32 bits of HMAC-SHA256 over a label and
+/// the tag's TID, so tags get distinct passwords that can be recomputed from the
+/// TID instead of being stored.
+#[cfg(feature = "password-derivation")]
+#[derive(Clone)]
+pub struct SiteKey(Vec<u8>);
+
+#[cfg(feature = "password-derivation")]
+impl SiteKey {
+    /// Site key from secret bytes
+    pub fn new(key: &[u8]) -> Self {
+        Self(key.to_vec())
+    }
+
+    /// Access password for the tag with this TID
+    pub fn access_password(&self, tid: &[u8]) -> Password {
+        self.derive(b"access", tid)
+    }
+
+    /// Kill password for the tag with this TID
+    pub fn kill_password(&self, tid: &[u8]) -> Password {
+        self.derive(b"kill", tid)
+    }
+
+    fn derive(&self, label: &[u8], tid: &[u8]) -> Password {
+        use hmac::{Hmac, Mac};
+
+        let mut mac = Hmac::<sha2::Sha256>::new_from_slice(&self.0)
+            .expect("HMAC accepts keys of any length");
+        mac.update(label);
+        mac.update(&[0]);
+        mac.update(tid);
+        let digest = mac.finalize().into_bytes();
+
+        // A zero password would leave the tag unprotected
+        digest
+            .chunks_exact(4)
+            .map(|chunk| Password::new([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .find(|password| !password.is_zero())
+            .unwrap_or(Password::new([0, 0, 0, 1]))
+    }
+}
+
+#[cfg(feature = "password-derivation")]
+impl core::fmt::Debug for SiteKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("SiteKey(..)")
+    }
+}
+
 /// RF link profile for modulation settings
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
@@ -369,17 +1223,316 @@ impl TryFrom<u8> for RfLinkProfile {
     }
 }
 
-/// QT control settings for Impinj Monza tags
+/// Receiver mixer gain
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MixerGain {
+    /// 0 dB
+    Db0 = 0x00,
+    /// 3 dB
+    Db3 = 0x01,
+    /// 6 dB
+    Db6 = 0x02,
+    /// 9 dB
+    Db9 = 0x03,
+    /// 12 dB
+    Db12 = 0x04,
+    /// 15 dB
+    Db15 = 0x05,
+    /// 16 dB
+    Db16 = 0x06,
+}
+
+impl MixerGain {
+    /// Gain in dB
+    pub fn db(&self) -> u8 {
+        match self {
+            MixerGain::Db0 => 0,
+            MixerGain::Db3 => 3,
+            MixerGain::Db6 => 6,
+            MixerGain::Db9 => 9,
+            MixerGain::Db12 => 12,
+            MixerGain::Db15 => 15,
+            MixerGain::Db16 => 16,
+        }
+    }
+}
+
+impl TryFrom<u8> for MixerGain {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(MixerGain::Db0),
+            0x01 => Ok(MixerGain::Db3),
+            0x02 => Ok(MixerGain::Db6),
+            0x03 => Ok(MixerGain::Db9),
+            0x04 => Ok(MixerGain::Db12),
+            0x05 => Ok(MixerGain::Db15),
+            0x06 => Ok(MixerGain::Db16),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Receiver IF amplifier gain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum IfGain {
+    /// 12 dB
+    Db12 = 0x00,
+    /// 18 dB
+    Db18 = 0x01,
+    /// 21 dB
+    Db21 = 0x02,
+    /// 24 dB
+    Db24 = 0x03,
+    /// 27 dB
+    Db27 = 0x04,
+    /// 30 dB
+    Db30 = 0x05,
+    /// 36 dB
+    Db36 = 0x06,
+    /// 40 dB
+    Db40 = 0x07,
+}
+
+impl IfGain {
+    /// Gain in dB
+    pub fn db(&self) -> u8 {
+        match self {
+            IfGain::Db12 => 12,
+            IfGain::Db18 => 18,
+            IfGain::Db21 => 21,
+            IfGain::Db24 => 24,
+            IfGain::Db27 => 27,
+            IfGain::Db30 => 30,
+            IfGain::Db36 => 36,
+            IfGain::Db40 => 40,
+        }
+    }
+}
+
+impl TryFrom<u8> for IfGain {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(IfGain::Db12),
+            0x01 => Ok(IfGain::Db18),
+            0x02 => Ok(IfGain::Db21),
+            0x03 => Ok(IfGain::Db24),
+            0x04 => Ok(IfGain::Db27),
+            0x05 => Ok(IfGain::Db30),
+            0x06 => Ok(IfGain::Db36),
+            0x07 => Ok(IfGain::Db40),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Receiver demodulator parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DemodulatorParams {
+    /// Mixer gain
+    pub mixer_gain: MixerGain,
+    /// IF amplifier gain
+    pub if_gain: IfGain,
+    /// Signal demodulation threshold (lower = weaker tags demodulated, less stable)
+    pub threshold: u16,
+}
+
+impl DemodulatorParams {
+    /// Lowest threshold recommended by the module vendor
+    pub const MIN_RECOMMENDED_THRESHOLD: u16 = 0x01B0;
+
+    /// Encode to 4-byte protocol format
+    pub fn to_bytes(&self) -> [u8; 4] {
+        let [threshold_msb, threshold_lsb] = self.threshold.to_be_bytes();
+        [self.mixer_gain as u8, self.if_gain as u8, threshold_msb, threshold_lsb]
+    }
+}
+
+impl Default for DemodulatorParams {
+    fn default() -> Self {
+        Self {
+            mixer_gain: MixerGain::Db9,
+            if_gain: IfGain::Db36,
+            threshold: Self::MIN_RECOMMENDED_THRESHOLD,
+        }
+    }
+}
+
+impl TryFrom<[u8; 4]> for DemodulatorParams {
+    type Error = ();
+
+    /// Decode from 4-byte protocol format
+    fn try_from(bytes: [u8; 4]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            mixer_gain: MixerGain::try_from(bytes[0])?,
+            if_gain: IfGain::try_from(bytes[1])?,
+            threshold: u16::from_be_bytes([bytes[2], bytes[3]]),
+        })
+    }
+}
+
+/// IO port on the reader module
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum IoPin {
+    /// IO1
+    Io1 = 0x01,
+    /// IO2
+    Io2 = 0x02,
+    /// IO3
+    Io3 = 0x03,
+    /// IO4
+    Io4 = 0x04,
+}
+
+impl TryFrom<u8> for IoPin {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x01 => Ok(IoPin::Io1),
+            0x02 => Ok(IoPin::Io2),
+            0x03 => Ok(IoPin::Io3),
+            0x04 => Ok(IoPin::Io4),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Direction of an IO port
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum IoDirection {
+    /// Input mode
+    Input = 0x00,
+    /// Output mode
+    Output = 0x01,
+}
+
+/// Logic level of an IO port
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum IoLevel {
+    /// Low level
+    Low = 0x00,
+    /// High level
+    High = 0x01,
+}
+
+impl TryFrom<u8> for IoLevel {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(IoLevel::Low),
+            0x01 => Ok(IoLevel::High),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Named bits of the NXP UCODE configuration word
+///
+/// Bit positions follow the UCODE 8/9 datasheets, where the word sits at EPC bank
+/// bit address 200h and address 20Fh is the least significant bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u16)]
+pub enum NxpConfigFlag {
+    /// Brand identifier is backscattered (204h)
+    BrandId = 0x0800,
+    /// Maximum backscatter strength (206h)
+    MaxBackscatter = 0x0200,
+    /// Memory selector: expose User memory in place of part of the EPC (209h)
+    MemorySelect = 0x0040,
+    /// Tag focus: only answer S1 inventories while the flag is A (20Bh)
+    TagFocus = 0x0010,
+    /// PSF alarm: answer EAS_Alarm commands (20Fh)
+    PsfAlarm = 0x0001,
+}
+
+/// NXP UCODE configuration word
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct NxpConfigWord(u16);
+
+impl NxpConfigWord {
+    /// Configuration word from its raw value
+    pub const fn from_raw(raw: u16) -> Self {
+        Self(raw)
+    }
+
+    /// Raw 16-bit value
+    pub const fn raw(self) -> u16 {
+        self.0
+    }
+
+    /// Whether a flag is set
+    pub const fn is_set(self, flag: NxpConfigFlag) -> bool {
+        self.0 & flag as u16 != 0
+    }
+
+    /// Copy of the word with a flag set or cleared
+    pub const fn with(self, flag: NxpConfigFlag, enabled: bool) -> Self {
+        if enabled {
+            Self(self.0 | flag as u16)
+        } else {
+            Self(self.0 & !(flag as u16))
+        }
+    }
+
+    /// Bits ChangeConfig has to toggle to turn this word into `target`
+    pub const fn toggle_mask(self, target: Self) -> u16 {
+        self.0 ^ target.0
+    }
+}
+
+/// QT control settings for Impinj Monza tags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct QtControl {
-    /// Short Range mode: reduces backscatter strength
+    /// Short Range mode: reduces backscatter strength (QT_SR)
     pub short_range: bool,
+    /// Present the private memory map instead of the public one (QT_MEM)
+    pub private_memory: bool,
     /// QT persistence: false = temporary, true = permanent
+    ///
+    /// Only used when writing; settings read from a tag report false.
     pub persistence: bool,
 }
 
 impl QtControl {
-    /// Encode to protocol byte
+    const SHORT_RANGE: u16 = 0x8000;
+    const PRIVATE_MEMORY: u16 = 0x4000;
+
+    /// Decode the QT control word read from a tag
+    pub fn from_word(word: u16) -> Self {
+        Self {
+            short_range: word & Self::SHORT_RANGE != 0,
+            private_memory: word & Self::PRIVATE_MEMORY != 0,
+            persistence: false,
+        }
+    }
+
+    /// Encode the QT control word, QT_SR and QT_MEM in the two most significant bits
+    pub fn to_word(&self) -> u16 {
+        let mut word = 0;
+        if self.short_range {
+            word |= Self::SHORT_RANGE;
+        }
+        if self.private_memory {
+            word |= Self::PRIVATE_MEMORY;
+        }
+        word
+    }
+
+    /// Encode short range and persistence in one byte
+    ///
+    /// The module does not take this encoding; QT commands carry the persistence
+    /// flag and [`to_word`](Self::to_word).
+    #[deprecated(note = "use `to_word`; the persistence flag is sent separately")]
     pub fn to_byte(&self) -> u8 {
         let mut byte = 0u8;
         if self.short_range {
@@ -392,6 +1545,26 @@ impl QtControl {
     }
 }
 
+/// Impinj QT privacy profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QtProfile {
+    /// Public memory map, with access to the tag limited to short range
+    Public,
+    /// Private memory map at full range
+    Private,
+}
+
+impl QtProfile {
+    /// QT control settings for the profile
+    pub fn control(self, persistence: bool) -> QtControl {
+        QtControl {
+            short_range: self == Self::Public,
+            private_memory: self == Self::Private,
+            persistence,
+        }
+    }
+}
+
 /// Errors that can occur during RFID operations
 #[derive(Debug)]
 pub enum UhfError {
@@ -401,6 +1574,9 @@ pub enum UhfError {
     InvalidParameter(String),
     /// Invalid response received from the reader
     InvalidResponse(String),
+    /// Irreversible command not sent because of [`GuardMode::DryRun`]; holds the
+    /// command frame with passwords masked
+    DryRun(String),
 }
 
 /// Convert bytes to uppercase hex string
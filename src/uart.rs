@@ -4,6 +4,7 @@ use crate::transport::RfidTransport;
 use esp_idf_svc::hal::gpio::{self, InputPin, OutputPin};
 use esp_idf_svc::hal::peripheral::Peripheral;
 use esp_idf_svc::hal::uart::{self, UartDriver};
+use esp_idf_svc::hal::units::Hertz;
 use std::time::Duration;
 
 pub struct UartTransport<'a> {
@@ -48,4 +49,13 @@ impl RfidTransport for UartTransport<'_> {
     fn clear_input(&mut self) -> Result<(), Self::Error> {
         self.uart.clear_rx()
     }
+
+    fn set_host_baud(&mut self, baud_rate: u32) -> Result<bool, Self::Error> {
+        self.uart.change_baudrate(Hertz(baud_rate))?;
+        Ok(true)
+    }
+
+    fn host_baud(&self) -> Option<u32> {
+        self.uart.baudrate().ok().map(|hz| hz.0)
+    }
 }